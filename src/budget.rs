@@ -0,0 +1,303 @@
+//! Token budgets for agents and the session
+//!
+//! A [`BudgetTracker`] follows `AgentSpawned` to learn each agent's
+//! `token_budget` and parent, and `SessionConfigured` for the session-wide
+//! `token_budget`. Each `UsageUpdate` is charged to its agent, every ancestor
+//! of that agent, and the session, so a runaway worker also eats into its
+//! lead's budget. When a charge crosses a warning threshold or goes over a
+//! budget, [`BudgetTracker::apply`] returns the `BudgetWarning` or
+//! `BudgetExceeded` event for the orchestrator to emit.
+//!
+//! ```
+//! use warhorn::budget::BudgetTracker;
+//! use warhorn::{AgentConfig, AgentId, AgentRole, Event, SubmissionId, TokenUsage};
+//!
+//! let mut tracker = BudgetTracker::new();
+//! let agent_id = AgentId::new();
+//! tracker.apply(&Event::AgentSpawned {
+//!     sub_id: SubmissionId::new(),
+//!     agent_id,
+//!     parent_id: None,
+//!     role: AgentRole::Worker,
+//!     config: AgentConfig { token_budget: Some(1_000), ..Default::default() },
+//! });
+//!
+//! let emitted = tracker.apply(&Event::UsageUpdate {
+//!     sub_id: SubmissionId::new(),
+//!     agent_id: Some(agent_id),
+//!     model: None,
+//!     task_id: None,
+//!     usage: TokenUsage { total_tokens: 1_200, ..Default::default() },
+//! });
+//! assert!(matches!(emitted[..], [Event::BudgetExceeded { used: 1_200, budget: 1_000, .. }]));
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use crate::events::Event;
+use crate::ids::{AgentId, SubmissionId};
+
+/// Warning thresholds, in percent of the budget, used by [`BudgetTracker::new`]
+pub const DEFAULT_THRESHOLDS: [u8; 2] = [75, 90];
+
+#[derive(Debug, Clone, Default)]
+struct Account {
+    budget: Option<u64>,
+    used: u64,
+    /// Highest threshold already warned about
+    warned: u8,
+    exceeded: bool,
+}
+
+impl Account {
+    /// Add `tokens`, returning the budget event this charge triggers
+    ///
+    /// Only the highest newly reached threshold is reported, and nothing
+    /// after the budget has been exceeded once.
+    fn charge(
+        &mut self,
+        tokens: u64,
+        thresholds: &[u8],
+        sub_id: &SubmissionId,
+        agent_id: Option<AgentId>,
+    ) -> Option<Event> {
+        self.used = self.used.saturating_add(tokens);
+        let budget = self.budget?;
+        if self.exceeded {
+            return None;
+        }
+        if self.used > budget {
+            self.exceeded = true;
+            return Some(Event::BudgetExceeded {
+                sub_id: sub_id.clone(),
+                agent_id,
+                used: self.used,
+                budget,
+            });
+        }
+        let used = u128::from(self.used) * 100;
+        let crossed = |threshold: u8| used >= u128::from(budget) * u128::from(threshold);
+        let reached = thresholds
+            .iter()
+            .copied()
+            .filter(|&threshold| threshold > self.warned && crossed(threshold))
+            .max()?;
+        self.warned = reached;
+        Some(Event::BudgetWarning {
+            sub_id: sub_id.clone(),
+            agent_id,
+            used: self.used,
+            budget,
+            threshold: reached,
+        })
+    }
+}
+
+/// Tracks token use against agent and session budgets
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    thresholds: Vec<u8>,
+    session: Account,
+    agents: HashMap<AgentId, Account>,
+    parents: HashMap<AgentId, AgentId>,
+}
+
+impl Default for BudgetTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BudgetTracker {
+    /// Tracker warning at [`DEFAULT_THRESHOLDS`]
+    pub fn new() -> Self {
+        Self {
+            thresholds: DEFAULT_THRESHOLDS.to_vec(),
+            session: Account::default(),
+            agents: HashMap::new(),
+            parents: HashMap::new(),
+        }
+    }
+
+    /// Warn at these percentages instead; values outside 1..=100 are ignored
+    pub fn with_thresholds(mut self, thresholds: impl IntoIterator<Item = u8>) -> Self {
+        let mut thresholds: Vec<u8> =
+            thresholds.into_iter().filter(|t| (1..=100).contains(t)).collect();
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        self.thresholds = thresholds;
+        self
+    }
+
+    /// Fold one event in, returning the budget events it triggers
+    ///
+    /// Events come out in order: the agent's own budget, then each ancestor
+    /// from nearest to root, then the session.
+    pub fn apply(&mut self, event: &Event) -> Vec<Event> {
+        match event {
+            Event::SessionConfigured { config, .. } => {
+                self.session.budget = config.token_budget;
+                Vec::new()
+            }
+            Event::AgentSpawned { agent_id, parent_id, config, .. } => {
+                self.agents.entry(*agent_id).or_default().budget = config.token_budget;
+                match parent_id {
+                    Some(parent_id) => self.parents.insert(*agent_id, *parent_id),
+                    None => self.parents.remove(agent_id),
+                };
+                Vec::new()
+            }
+            Event::UsageUpdate { sub_id, agent_id, usage, .. } => {
+                let tokens = usage.total_tokens;
+                let mut emitted = Vec::new();
+                let chain = agent_id.map(|id| self.lineage(id)).unwrap_or_default();
+                for id in chain {
+                    let account = self.agents.entry(id).or_default();
+                    emitted.extend(account.charge(tokens, &self.thresholds, sub_id, Some(id)));
+                }
+                emitted.extend(self.session.charge(tokens, &self.thresholds, sub_id, None));
+                emitted
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// `agent_id` followed by its ancestors, nearest first
+    fn lineage(&self, agent_id: AgentId) -> Vec<AgentId> {
+        let mut seen = HashSet::new();
+        let mut chain = Vec::new();
+        let mut next = Some(agent_id);
+        while let Some(id) = next.filter(|id| seen.insert(*id)) {
+            chain.push(id);
+            next = self.parents.get(&id).copied();
+        }
+        chain
+    }
+
+    /// Tokens charged to an agent, including its descendants
+    pub fn used(&self, agent_id: &AgentId) -> u64 {
+        self.agents.get(agent_id).map_or(0, |account| account.used)
+    }
+
+    /// Tokens charged to the session
+    pub fn session_used(&self) -> u64 {
+        self.session.used
+    }
+
+    /// Tokens left in an agent's budget, or `None` if it has no budget
+    pub fn remaining(&self, agent_id: &AgentId) -> Option<u64> {
+        let account = self.agents.get(agent_id)?;
+        account.budget.map(|budget| budget.saturating_sub(account.used))
+    }
+
+    /// Check whether an agent has gone over its budget
+    pub fn is_exceeded(&self, agent_id: &AgentId) -> bool {
+        self.agents.get(agent_id).is_some_and(|account| account.exceeded)
+    }
+
+    /// Check whether the session has gone over its budget
+    pub fn is_session_exceeded(&self) -> bool {
+        self.session.exceeded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::SessionId;
+    use crate::models::{AgentConfig, AgentRole, SessionConfig, TokenUsage};
+
+    fn spawned(agent_id: AgentId, parent_id: Option<AgentId>, budget: Option<u64>) -> Event {
+        Event::AgentSpawned {
+            sub_id: SubmissionId::new(),
+            agent_id,
+            parent_id,
+            role: AgentRole::Worker,
+            config: AgentConfig { token_budget: budget, ..Default::default() },
+        }
+    }
+
+    fn usage(agent_id: Option<AgentId>, tokens: u64) -> Event {
+        Event::UsageUpdate {
+            sub_id: SubmissionId::new(),
+            agent_id,
+            model: None,
+            task_id: None,
+            usage: TokenUsage { total_tokens: tokens, ..Default::default() },
+        }
+    }
+
+    /// `(agent, threshold)` for warnings, `(agent, 0)` for exceeded
+    fn summary(events: &[Event]) -> Vec<(Option<AgentId>, u8)> {
+        events
+            .iter()
+            .map(|event| match event {
+                Event::BudgetWarning { agent_id, threshold, .. } => (*agent_id, *threshold),
+                Event::BudgetExceeded { agent_id, .. } => (*agent_id, 0),
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect()
+    }
+
+    // === Threshold Tests ===
+
+    #[test]
+    fn test_warns_once_per_threshold_then_exceeds_once() {
+        let agent = AgentId::new();
+        let mut tracker = BudgetTracker::new();
+        tracker.apply(&spawned(agent, None, Some(1_000)));
+
+        assert!(tracker.apply(&usage(Some(agent), 700)).is_empty());
+        assert_eq!(summary(&tracker.apply(&usage(Some(agent), 50))), [(Some(agent), 75)]);
+        assert!(tracker.apply(&usage(Some(agent), 10)).is_empty());
+        assert_eq!(summary(&tracker.apply(&usage(Some(agent), 140))), [(Some(agent), 90)]);
+        assert_eq!(tracker.remaining(&agent), Some(100));
+        assert_eq!(summary(&tracker.apply(&usage(Some(agent), 101))), [(Some(agent), 0)]);
+        assert!(tracker.apply(&usage(Some(agent), 500)).is_empty());
+        assert!(tracker.is_exceeded(&agent));
+        assert_eq!(tracker.used(&agent), 1_501);
+    }
+
+    #[test]
+    fn test_jumps_report_highest_threshold_only() {
+        let agent = AgentId::new();
+        let mut tracker = BudgetTracker::new().with_thresholds([50, 0, 80, 95, 150, 80]);
+        tracker.apply(&spawned(agent, None, Some(100)));
+
+        let emitted = tracker.apply(&usage(Some(agent), 96));
+        assert!(matches!(
+            emitted[..],
+            [Event::BudgetWarning { used: 96, budget: 100, threshold: 95, .. }]
+        ));
+        assert_eq!(summary(&tracker.apply(&usage(Some(agent), 5))), [(Some(agent), 0)]);
+    }
+
+    // === Attribution Tests ===
+
+    #[test]
+    fn test_usage_charges_ancestors_and_session() {
+        let root = AgentId::new();
+        let lead = AgentId::new();
+        let worker = AgentId::new();
+        let mut tracker = BudgetTracker::new();
+        tracker.apply(&Event::SessionConfigured {
+            sub_id: SubmissionId::new(),
+            session_id: SessionId::new(),
+            config: SessionConfig { token_budget: Some(10_000), ..Default::default() },
+        });
+        tracker.apply(&spawned(root, None, Some(5_000)));
+        tracker.apply(&spawned(lead, Some(root), Some(1_000)));
+        tracker.apply(&spawned(worker, Some(lead), None));
+
+        let emitted = tracker.apply(&usage(Some(worker), 4_000));
+        assert_eq!(summary(&emitted), [(Some(lead), 0), (Some(root), 75)]);
+        assert_eq!(tracker.used(&worker), 4_000);
+        assert_eq!(tracker.remaining(&worker), None);
+
+        let emitted = tracker.apply(&usage(None, 5_500));
+        assert_eq!(summary(&emitted), [(None, 90)]);
+        assert_eq!(tracker.used(&root), 4_000);
+        assert_eq!(tracker.session_used(), 9_500);
+        assert!(!tracker.is_session_exceeded());
+    }
+}
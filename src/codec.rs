@@ -0,0 +1,353 @@
+//! Newline-delimited JSON codec
+//!
+//! Reads and writes streams of protocol messages as one JSON document per
+//! line over any [`std::io::Read`] / [`std::io::Write`], such as stdio or a
+//! Unix socket. A malformed or oversized line is reported as an error for
+//! that line only; the reader resynchronises on the next newline and keeps
+//! going.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::marker::PhantomData;
+
+use crate::error::ProtocolError;
+
+/// Default maximum size of a single frame (8 MiB)
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 8 * 1024 * 1024;
+
+/// Writes messages as newline-delimited JSON
+#[derive(Debug)]
+pub struct NdjsonWriter<W, T> {
+    inner: W,
+    max_frame_size: usize,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<W: Write, T: Serialize> NdjsonWriter<W, T> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the maximum size of an encoded frame, excluding the newline
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// Encode and write one message, followed by a newline, then flush
+    pub fn write(&mut self, message: &T) -> Result<(), ProtocolError> {
+        let mut line = serde_json::to_vec(message)?;
+        if line.len() > self.max_frame_size {
+            return Err(ProtocolError::FrameTooLarge {
+                size: line.len(),
+                max: self.max_frame_size,
+            });
+        }
+        line.push(b'\n');
+
+        self.inner.write_all(&line).map_err(io_error)?;
+        self.inner.flush().map_err(io_error)
+    }
+
+    /// Get a reference to the underlying writer
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Unwrap the underlying writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Reads messages from newline-delimited JSON
+///
+/// Iterating yields one `Result` per non-blank line. Errors for individual
+/// lines do not end the stream; EOF does, and so does an I/O error, which is
+/// yielded once.
+#[derive(Debug)]
+pub struct NdjsonReader<R, T> {
+    inner: BufReader<R>,
+    max_frame_size: usize,
+    line_number: usize,
+    buf: Vec<u8>,
+    /// Set after an I/O error, since the underlying reader may keep failing
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T: DeserializeOwned> NdjsonReader<R, T> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner: BufReader::new(inner),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            line_number: 0,
+            buf: Vec::new(),
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the maximum size of a frame, excluding the newline
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// Line number of the most recently read line (1-based)
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Read the next message
+    ///
+    /// Returns `None` at EOF and after an I/O error. Blank lines are skipped.
+    pub fn read(&mut self) -> Option<Result<T, ProtocolError>> {
+        if self.done {
+            return None;
+        }
+        loop {
+            let size = match self.read_line() {
+                Ok(Some(size)) => size,
+                Ok(None) => return None,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(io_error(e)));
+                }
+            };
+            self.line_number += 1;
+
+            let decoded = decode_line(&self.buf, size, self.max_frame_size, self.line_number);
+            if let Some(result) = decoded {
+                return Some(result);
+            }
+        }
+    }
+
+    /// Read one line into `self.buf`, returning its full length
+    ///
+    /// At most `max_frame_size + 1` bytes are buffered; the rest of an
+    /// oversized line is consumed and discarded so the next read starts on a
+    /// fresh line.
+    fn read_line(&mut self) -> io::Result<Option<usize>> {
+        self.buf.clear();
+        let mut size = 0;
+        let mut saw_any = false;
+
+        loop {
+            let available = match self.inner.fill_buf() {
+                Ok(available) => available,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if available.is_empty() {
+                return Ok(saw_any.then_some(size));
+            }
+            saw_any = true;
+
+            let chunk = buffer_line_chunk(available, &mut self.buf, self.max_frame_size);
+            size += chunk.len;
+
+            self.inner.consume(chunk.consumed);
+            if chunk.found_newline {
+                return Ok(Some(size));
+            }
+        }
+    }
+}
+
+impl<R: Read, T: DeserializeOwned> Iterator for NdjsonReader<R, T> {
+    type Item = Result<T, ProtocolError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read()
+    }
+}
+
+/// How much of a read buffer belonged to the current line
+pub(crate) struct LineChunk {
+    /// Bytes of line content, excluding the newline
+    pub len: usize,
+    /// Bytes to consume from the read buffer
+    pub consumed: usize,
+    /// Whether the line ended in this chunk
+    pub found_newline: bool,
+}
+
+/// Append the part of `available` up to the next newline to `buf`
+///
+/// Never grows `buf` beyond `max_frame_size + 1` bytes, which is enough to
+/// detect an oversized line without holding all of it.
+pub(crate) fn buffer_line_chunk(
+    available: &[u8],
+    buf: &mut Vec<u8>,
+    max_frame_size: usize,
+) -> LineChunk {
+    let (len, consumed) = match available.iter().position(|&b| b == b'\n') {
+        Some(pos) => (pos, pos + 1),
+        None => (available.len(), available.len()),
+    };
+
+    let room = (max_frame_size + 1).saturating_sub(buf.len());
+    buf.extend_from_slice(&available[..len.min(room)]);
+
+    LineChunk { len, consumed, found_newline: consumed > len }
+}
+
+/// Decode one line read by a line reader
+///
+/// Returns `None` for blank lines, which carry no message.
+pub(crate) fn decode_line<T: DeserializeOwned>(
+    buf: &[u8],
+    size: usize,
+    max_frame_size: usize,
+    line_number: usize,
+) -> Option<Result<T, ProtocolError>> {
+    if size > max_frame_size {
+        return Some(Err(ProtocolError::FrameTooLarge { size, max: max_frame_size }));
+    }
+
+    let line = buf.strip_suffix(b"\r").unwrap_or(buf);
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return None;
+    }
+
+    Some(serde_json::from_slice(line).map_err(|e| ProtocolError::DeserializationError {
+        message: format!("line {}: {}", line_number, e),
+    }))
+}
+
+fn io_error(e: io::Error) -> ProtocolError {
+    ProtocolError::TransportError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Event, Op, SubmissionId};
+
+    fn write_all(ops: &[Op]) -> Vec<u8> {
+        let mut writer = NdjsonWriter::new(Vec::new());
+        for op in ops {
+            writer.write(op).unwrap();
+        }
+        writer.into_inner()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let ops = vec![Op::user_input("one"), Op::interrupt(), Op::user_input("two")];
+        let bytes = write_all(&ops);
+        assert_eq!(bytes.iter().filter(|&&b| b == b'\n').count(), 3);
+
+        let parsed: Vec<Op> = NdjsonReader::new(bytes.as_slice())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(parsed.len(), 3);
+        for (a, b) in ops.iter().zip(&parsed) {
+            assert_eq!(a.sub_id(), b.sub_id());
+        }
+    }
+
+    #[test]
+    fn test_skips_blank_lines_and_crlf() {
+        let input = concat!(
+            "\n{\"type\":\"undo\",\"sub_id\":\"a\"}\r\n",
+            "\r\n{\"type\":\"undo\",\"sub_id\":\"b\"}"
+        );
+        let parsed: Vec<Op> = NdjsonReader::new(input.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let ids: Vec<_> = parsed.iter().map(|op| op.sub_id().as_str().to_string()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_malformed_line_resyncs() {
+        let input = concat!(
+            "{\"type\":\"undo\",\"sub_id\":\"a\"}\n",
+            "{not json\n",
+            "{\"type\":\"undo\",\"sub_id\":\"c\"}\n",
+        );
+        let mut reader = NdjsonReader::<_, Op>::new(input.as_bytes());
+
+        assert!(reader.read().unwrap().is_ok());
+        match reader.read().unwrap() {
+            Err(ProtocolError::DeserializationError { message }) => {
+                assert!(message.starts_with("line 2:"), "{}", message);
+            }
+            other => panic!("Expected DeserializationError, got {:?}", other),
+        }
+        let op = reader.read().unwrap().unwrap();
+        assert_eq!(op.sub_id().as_str(), "c");
+        assert!(reader.read().is_none());
+    }
+
+    #[test]
+    fn test_io_error_ends_stream() {
+        struct BrokenPipe;
+
+        impl Read for BrokenPipe {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+        }
+
+        let results: Vec<_> = NdjsonReader::<_, Op>::new(BrokenPipe).take(3).collect();
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], Err(ProtocolError::TransportError(_))));
+    }
+
+    #[test]
+    fn test_oversized_line_resyncs() {
+        let big = Op::user_input("x".repeat(1000));
+        let small = Op::Undo { sub_id: SubmissionId::from_string("s") };
+        let bytes = write_all(&[big, small]);
+
+        let mut reader = NdjsonReader::<_, Op>::new(bytes.as_slice()).with_max_frame_size(100);
+        match reader.read().unwrap() {
+            Err(ProtocolError::FrameTooLarge { size, max }) => {
+                assert!(size > 1000);
+                assert_eq!(max, 100);
+            }
+            other => panic!("Expected FrameTooLarge, got {:?}", other),
+        }
+        let op = reader.read().unwrap().unwrap();
+        assert_eq!(op.sub_id().as_str(), "s");
+        assert_eq!(reader.line_number(), 2);
+    }
+
+    #[test]
+    fn test_writer_rejects_oversized_frame() {
+        let mut writer = NdjsonWriter::new(Vec::new()).with_max_frame_size(10);
+        let result = writer.write(&Op::user_input("too long for ten bytes"));
+        assert!(matches!(result, Err(ProtocolError::FrameTooLarge { .. })));
+        assert!(writer.get_ref().is_empty());
+    }
+
+    #[test]
+    fn test_event_stream() {
+        let mut writer = NdjsonWriter::new(Vec::new());
+        writer
+            .write(&Event::Warning {
+                sub_id: SubmissionId::new(),
+                message: "multi\nline".into(),
+                details: None,
+            })
+            .unwrap();
+        let bytes = writer.into_inner();
+
+        let events: Vec<Event> = NdjsonReader::new(bytes.as_slice())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        match &events[..] {
+            [Event::Warning { message, .. }] => assert_eq!(message, "multi\nline"),
+            other => panic!("Unexpected events: {:?}", other),
+        }
+    }
+}
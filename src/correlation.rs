@@ -0,0 +1,461 @@
+//! Matching events to the operations that caused them
+//!
+//! Every `Op` carries a `SubmissionId` and every `Event` it triggers echoes
+//! it back. A [`Correlator`] tracks outgoing ops, collects the events for each
+//! one, and reports when the exchange is over: when a terminal event arrives
+//! (see [`is_terminal`]), when the op times out, or when the connection
+//! closes before any answer came back.
+//!
+//! ```
+//! use warhorn::correlation::{Correlator, Routed};
+//! use warhorn::{Event, Op, TaskId};
+//!
+//! let mut correlator = Correlator::new();
+//! let op = Op::user_input("Add authentication");
+//! let sub_id = op.sub_id().clone();
+//! correlator.register(op).unwrap();
+//!
+//! let done = Event::TaskInterrupted { sub_id, task_id: TaskId::new() };
+//! assert!(matches!(correlator.route(done), Routed::Finished(_)));
+//! ```
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::error::ProtocolError;
+use crate::events::Event;
+use crate::ids::SubmissionId;
+use crate::ops::Op;
+
+/// Default time an op may wait for its terminal event
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Check whether `op` expects any reply at all
+///
+/// `RouteMessage` is fire-and-forget; every other op is answered by at least
+/// one event.
+pub fn expects_reply(op: &Op) -> bool {
+    !matches!(op, Op::RouteMessage { .. })
+}
+
+/// Check whether `event` ends the exchange started by `op`
+///
+/// A non-recoverable `Event::Error` ends any exchange, and any `Error` ends
+/// a plan review (`ApprovePlan`, `RejectPlan`, `RevisePlan`), which is how
+/// the orchestrator answers when no plan is pending. Otherwise:
+///
+/// | Op | Terminal events |
+/// |----|-----------------|
+/// | `Hello` | `Welcome` |
+/// | `ConfigureSession` | `SessionConfigured` |
+/// | `UserInput` | `TaskComplete`, `TaskFailed`, `TaskInterrupted` |
+/// | `Interrupt` | `TaskInterrupted` |
+/// | `ExecApproval`, `McpApproval` | `ToolCallComplete`, `ToolCallFailed` |
+/// | `SpawnAgent` | `AgentSpawned` |
+/// | `TerminateAgent` | `AgentTerminated` |
+/// | `SaveCheckpoint` | `CheckpointSaved` |
+/// | `RestoreCheckpoint`, `Undo` | `CheckpointRestored` |
+/// | `ListCheckpoints` | `CheckpointList` |
+/// | `TogglePlanMode` | `PlanModeChanged` |
+/// | `ApprovePlan` | `PlanApproved` |
+/// | `RejectPlan` | `PlanCreated`, `PlanRevised` |
+/// | `RevisePlan` | `PlanRevised` |
+/// | `UpdateSettings` | `SettingsUpdated` |
+pub fn is_terminal(op: &Op, event: &Event) -> bool {
+    if matches!(event, Event::Error { recoverable: false, .. }) {
+        return true;
+    }
+
+    let plan_review =
+        matches!(op, Op::ApprovePlan { .. } | Op::RejectPlan { .. } | Op::RevisePlan { .. });
+    if plan_review && matches!(event, Event::Error { .. }) {
+        return true;
+    }
+
+    match op {
+        Op::Hello { .. } => matches!(event, Event::Welcome { .. }),
+        Op::ConfigureSession { .. } => matches!(event, Event::SessionConfigured { .. }),
+        Op::UserInput { .. } => matches!(
+            event,
+            Event::TaskComplete { .. } | Event::TaskFailed { .. } | Event::TaskInterrupted { .. }
+        ),
+        Op::Interrupt { .. } => matches!(event, Event::TaskInterrupted { .. }),
+        Op::ExecApproval { .. } | Op::McpApproval { .. } => matches!(
+            event,
+            Event::ToolCallComplete { .. } | Event::ToolCallFailed { .. }
+        ),
+        Op::SpawnAgent { .. } => matches!(event, Event::AgentSpawned { .. }),
+        Op::TerminateAgent { .. } => matches!(event, Event::AgentTerminated { .. }),
+        Op::RouteMessage { .. } => false,
+        Op::SaveCheckpoint { .. } => matches!(event, Event::CheckpointSaved { .. }),
+        Op::RestoreCheckpoint { .. } | Op::Undo { .. } => {
+            matches!(event, Event::CheckpointRestored { .. })
+        }
+        Op::ListCheckpoints { .. } => matches!(event, Event::CheckpointList { .. }),
+        Op::TogglePlanMode { .. } => matches!(event, Event::PlanModeChanged { .. }),
+        Op::ApprovePlan { .. } => matches!(event, Event::PlanApproved { .. }),
+        Op::RejectPlan { .. } => {
+            matches!(event, Event::PlanCreated { .. } | Event::PlanRevised { .. })
+        }
+        Op::RevisePlan { .. } => matches!(event, Event::PlanRevised { .. }),
+        Op::UpdateSettings { .. } => matches!(event, Event::SettingsUpdated { .. }),
+    }
+}
+
+/// How an exchange ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// A terminal event arrived
+    Completed,
+    /// The deadline passed before a terminal event
+    TimedOut,
+    /// The correlator was closed before a terminal event
+    Unanswered,
+}
+
+/// An op together with every event received for it
+#[derive(Debug, Clone)]
+pub struct Exchange {
+    /// The operation that was sent
+    pub op: Op,
+    /// Events received for it, in arrival order
+    pub events: Vec<Event>,
+    /// How the exchange ended
+    pub outcome: Outcome,
+}
+
+impl Exchange {
+    /// Submission ID shared by the op and its events
+    pub fn sub_id(&self) -> &SubmissionId {
+        self.op.sub_id()
+    }
+
+    /// The event that ended the exchange, if it completed
+    pub fn terminal_event(&self) -> Option<&Event> {
+        match self.outcome {
+            Outcome::Completed => self.events.last(),
+            _ => None,
+        }
+    }
+
+    /// Check whether no event at all was received for the op
+    pub fn is_unanswered(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+/// Where [`Correlator::route`] put an event
+#[derive(Debug, Clone)]
+pub enum Routed {
+    /// Added to an open exchange that is still waiting for more
+    Pending,
+    /// Ended an exchange
+    Finished(Exchange),
+    /// No open op has this event's `SubmissionId`
+    Unsolicited(Event),
+}
+
+#[derive(Debug)]
+struct Open {
+    op: Op,
+    events: Vec<Event>,
+    deadline: Instant,
+}
+
+/// Tracks outgoing ops and routes incoming events to them
+#[derive(Debug)]
+pub struct Correlator {
+    open: HashMap<SubmissionId, Open>,
+    timeout: Duration,
+}
+
+impl Correlator {
+    pub fn new() -> Self {
+        Self {
+            open: HashMap::new(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Set how long an op may wait for its terminal event
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Start tracking an outgoing op
+    ///
+    /// Returns `Ok(false)` without tracking anything for ops that expect no
+    /// reply, and [`ProtocolError::InvalidSubmissionId`] if an op with the
+    /// same `SubmissionId` is still open.
+    pub fn register(&mut self, op: Op) -> Result<bool, ProtocolError> {
+        self.register_at(op, Instant::now())
+    }
+
+    /// Start tracking an op sent at `sent_at`
+    pub fn register_at(&mut self, op: Op, sent_at: Instant) -> Result<bool, ProtocolError> {
+        if !expects_reply(&op) {
+            return Ok(false);
+        }
+        let sub_id = op.sub_id().clone();
+        if self.open.contains_key(&sub_id) {
+            return Err(ProtocolError::InvalidSubmissionId(format!(
+                "{sub_id} is already awaiting a reply"
+            )));
+        }
+
+        let deadline = sent_at + self.timeout;
+        self.open.insert(sub_id, Open { op, events: Vec::new(), deadline });
+        Ok(true)
+    }
+
+    /// Route an incoming event to the op it answers
+    pub fn route(&mut self, event: Event) -> Routed {
+        let sub_id = event.sub_id().clone();
+        let Some(open) = self.open.get_mut(&sub_id) else {
+            return Routed::Unsolicited(event);
+        };
+
+        let terminal = is_terminal(&open.op, &event);
+        open.events.push(event);
+        if !terminal {
+            return Routed::Pending;
+        }
+
+        let open = self.open.remove(&sub_id).expect("exchange is open");
+        Routed::Finished(close(open, Outcome::Completed))
+    }
+
+    /// Remove and return every op whose deadline is at or before `now`
+    pub fn expire(&mut self, now: Instant) -> Vec<Exchange> {
+        let expired: Vec<SubmissionId> = self
+            .open
+            .iter()
+            .filter(|(_, open)| open.deadline <= now)
+            .map(|(sub_id, _)| sub_id.clone())
+            .collect();
+
+        expired
+            .into_iter()
+            .filter_map(|sub_id| self.open.remove(&sub_id))
+            .map(|open| close(open, Outcome::TimedOut))
+            .collect()
+    }
+
+    /// Stop tracking everything, returning the ops that are still open
+    ///
+    /// Call this when the connection closes.
+    pub fn close(&mut self) -> Vec<Exchange> {
+        self.open
+            .drain()
+            .map(|(_, open)| close(open, Outcome::Unanswered))
+            .collect()
+    }
+
+    /// Check whether an op is still waiting for its terminal event
+    pub fn is_pending(&self, sub_id: &SubmissionId) -> bool {
+        self.open.contains_key(sub_id)
+    }
+
+    /// Events received so far for an open op
+    pub fn events(&self, sub_id: &SubmissionId) -> Option<&[Event]> {
+        self.open.get(sub_id).map(|open| open.events.as_slice())
+    }
+
+    /// Number of open ops
+    pub fn len(&self) -> usize {
+        self.open.len()
+    }
+
+    /// Check whether no ops are open
+    pub fn is_empty(&self) -> bool {
+        self.open.is_empty()
+    }
+}
+
+impl Default for Correlator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn close(open: Open, outcome: Outcome) -> Exchange {
+    Exchange { op: open.op, events: open.events, outcome }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{all_events, all_ops};
+    use crate::ids::*;
+
+    fn started(sub_id: &SubmissionId) -> Event {
+        Event::TaskStarted {
+            sub_id: sub_id.clone(),
+            task_id: TaskId::new(),
+            prompt: "go".into(),
+        }
+    }
+
+    fn failed(sub_id: &SubmissionId) -> Event {
+        Event::TaskFailed {
+            sub_id: sub_id.clone(),
+            task_id: TaskId::new(),
+            error: "boom".into(),
+        }
+    }
+
+    // === Routing Tests ===
+
+    #[test]
+    fn test_user_input_exchange() {
+        let mut correlator = Correlator::new();
+        let op = Op::user_input("go");
+        let sub_id = op.sub_id().clone();
+        assert!(correlator.register(op).unwrap());
+
+        assert!(matches!(correlator.route(started(&sub_id)), Routed::Pending));
+        assert_eq!(correlator.events(&sub_id).unwrap().len(), 1);
+
+        let Routed::Finished(exchange) = correlator.route(failed(&sub_id)) else {
+            panic!("expected the exchange to finish");
+        };
+        assert_eq!(exchange.outcome, Outcome::Completed);
+        assert_eq!(exchange.events.len(), 2);
+        assert!(matches!(exchange.terminal_event(), Some(Event::TaskFailed { .. })));
+        assert!(!correlator.is_pending(&sub_id));
+    }
+
+    #[test]
+    fn test_unsolicited_event() {
+        let mut correlator = Correlator::new();
+        let event = started(&SubmissionId::new());
+        assert!(matches!(correlator.route(event), Routed::Unsolicited(_)));
+    }
+
+    #[test]
+    fn test_events_after_terminal_are_unsolicited() {
+        let mut correlator = Correlator::new();
+        let op = Op::user_input("go");
+        let sub_id = op.sub_id().clone();
+        correlator.register(op).unwrap();
+
+        assert!(matches!(correlator.route(failed(&sub_id)), Routed::Finished(_)));
+        assert!(matches!(correlator.route(started(&sub_id)), Routed::Unsolicited(_)));
+    }
+
+    #[test]
+    fn test_fatal_error_ends_any_exchange() {
+        let mut correlator = Correlator::new();
+        let op = Op::ListCheckpoints { sub_id: SubmissionId::new() };
+        let sub_id = op.sub_id().clone();
+        correlator.register(op).unwrap();
+
+        let warning =
+            Event::Error { sub_id: sub_id.clone(), message: "retry".into(), recoverable: true };
+        assert!(matches!(correlator.route(warning), Routed::Pending));
+
+        let fatal = Event::Error { sub_id, message: "gone".into(), recoverable: false };
+        assert!(matches!(correlator.route(fatal), Routed::Finished(_)));
+    }
+
+    #[test]
+    fn test_duplicate_registration_rejected() {
+        let mut correlator = Correlator::new();
+        let op = Op::user_input("go");
+        correlator.register(op.clone()).unwrap();
+        assert!(matches!(
+            correlator.register(op),
+            Err(ProtocolError::InvalidSubmissionId(_))
+        ));
+    }
+
+    #[test]
+    fn test_fire_and_forget_not_tracked() {
+        let mut correlator = Correlator::new();
+        let op = Op::RouteMessage {
+            sub_id: SubmissionId::new(),
+            agent_id: AgentId::new(),
+            content: "hi".into(),
+        };
+        assert!(!correlator.register(op).unwrap());
+        assert!(correlator.is_empty());
+    }
+
+    // === Terminal Table Tests ===
+
+    #[test]
+    fn test_terminal_table() {
+        let ops = all_ops();
+        let events = all_events();
+        let terminal_names = |op: &Op| -> Vec<String> {
+            events
+                .iter()
+                .filter(|event| is_terminal(op, event))
+                .map(|event| {
+                    serde_json::to_value(event).unwrap()["type"].as_str().unwrap().to_string()
+                })
+                .collect()
+        };
+
+        let find = |tag: &str| {
+            ops.iter()
+                .find(|op| serde_json::to_value(op).unwrap()["type"] == tag)
+                .unwrap()
+        };
+
+        assert_eq!(
+            terminal_names(find("user_input")),
+            ["task_complete", "task_failed", "task_interrupted"]
+        );
+        assert_eq!(terminal_names(find("list_checkpoints")), ["checkpoint_list"]);
+        assert_eq!(terminal_names(find("restore_checkpoint")), ["checkpoint_restored"]);
+        assert_eq!(terminal_names(find("hello")), ["welcome"]);
+        assert_eq!(terminal_names(find("approve_plan")), ["plan_approved", "error"]);
+        assert_eq!(terminal_names(find("reject_plan")), ["plan_created", "plan_revised", "error"]);
+        assert!(terminal_names(find("route_message")).is_empty());
+
+        // The fixture error is recoverable, so outside plan reviews this
+        // only counts the op-specific terminals
+        for op in ops.iter().filter(|op| expects_reply(op)) {
+            assert!(!terminal_names(op).is_empty(), "{op:?} has no terminal event");
+        }
+    }
+
+    // === Timeout Tests ===
+
+    #[test]
+    fn test_expire() {
+        let mut correlator = Correlator::new().with_timeout(Duration::from_secs(10));
+        let start = Instant::now();
+
+        let answered = Op::user_input("answered");
+        let answered_id = answered.sub_id().clone();
+        correlator.register_at(answered, start).unwrap();
+        correlator.route(started(&answered_id));
+
+        let silent = Op::user_input("silent");
+        correlator.register_at(silent, start + Duration::from_secs(5)).unwrap();
+
+        assert!(correlator.expire(start + Duration::from_secs(9)).is_empty());
+
+        let expired = correlator.expire(start + Duration::from_secs(10));
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].sub_id(), &answered_id);
+        assert_eq!(expired[0].outcome, Outcome::TimedOut);
+        assert!(!expired[0].is_unanswered());
+        assert_eq!(correlator.len(), 1);
+    }
+
+    #[test]
+    fn test_close_reports_unanswered() {
+        let mut correlator = Correlator::new();
+        correlator.register(Op::interrupt()).unwrap();
+        correlator.register(Op::user_input("go")).unwrap();
+
+        let left = correlator.close();
+        assert_eq!(left.len(), 2);
+        assert!(left.iter().all(|e| e.outcome == Outcome::Unanswered && e.is_unanswered()));
+        assert!(correlator.is_empty());
+    }
+}
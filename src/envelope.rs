@@ -0,0 +1,328 @@
+//! Versioned wire envelope for protocol messages
+//!
+//! Every `Op` and `Event` put on the wire is wrapped in an [`Envelope`] that
+//! carries the protocol version, a per-connection sequence number, and the
+//! time it was sent. Peers check the version before touching the payload, so
+//! skew between UI and orchestrator surfaces as
+//! [`ProtocolError::VersionMismatch`] instead of an opaque serde error.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use chrono::{DateTime, Utc};
+
+use crate::error::ProtocolError;
+use crate::PROTOCOL_VERSION;
+
+// === Protocol Version ===
+
+/// A `major.minor.patch` protocol version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ProtocolVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl ProtocolVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self { major, minor, patch }
+    }
+
+    /// The version implemented by this crate (see [`PROTOCOL_VERSION`])
+    pub fn current() -> Self {
+        PROTOCOL_VERSION
+            .parse()
+            .expect("PROTOCOL_VERSION is a valid version")
+    }
+
+    /// Check whether two versions can talk to each other
+    ///
+    /// Versions are compatible when their major versions match.
+    pub fn is_compatible_with(&self, other: &ProtocolVersion) -> bool {
+        self.major == other.major
+    }
+
+    /// Return an error if `other` is not compatible with this version
+    pub fn check_compatible(&self, other: &ProtocolVersion) -> Result<(), ProtocolError> {
+        if self.is_compatible_with(other) {
+            Ok(())
+        } else {
+            Err(ProtocolError::VersionMismatch {
+                expected: self.to_string(),
+                actual: other.to_string(),
+            })
+        }
+    }
+}
+
+impl Default for ProtocolVersion {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
+impl fmt::Display for ProtocolVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for ProtocolVersion {
+    type Err = ProtocolError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ProtocolError::DeserializationError {
+            message: format!("invalid protocol version: {s:?}"),
+        };
+
+        let mut parts = s.split('.');
+        let mut next = || -> Result<u32, ProtocolError> {
+            parts.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())
+        };
+        let version = Self::new(next()?, next()?, next()?);
+
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(version)
+    }
+}
+
+impl Serialize for ProtocolVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ProtocolVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for ProtocolVersion {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "ProtocolVersion".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "A `major.minor.patch` protocol version",
+            "type": "string",
+            "pattern": r"^\d+\.\d+\.\d+$"
+        })
+    }
+}
+
+// === Envelope ===
+
+/// Wire wrapper around an `Op` or `Event`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Envelope<T> {
+    /// Protocol version of the sender
+    pub version: ProtocolVersion,
+    /// Per-connection monotonic sequence number
+    pub seq: u64,
+    /// When the message was sent
+    pub sent_at: DateTime<Utc>,
+    /// The wrapped message
+    pub payload: T,
+}
+
+impl<T> Envelope<T> {
+    /// Wrap a payload with the current protocol version
+    pub fn new(seq: u64, payload: T) -> Self {
+        Self {
+            version: ProtocolVersion::current(),
+            seq,
+            sent_at: Utc::now(),
+            payload,
+        }
+    }
+
+    /// Unwrap the payload
+    pub fn into_payload(self) -> T {
+        self.payload
+    }
+}
+
+impl<T: Serialize> Envelope<T> {
+    /// Serialize this envelope to JSON
+    pub fn encode(&self) -> Result<String, ProtocolError> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+impl<T: DeserializeOwned> Envelope<T> {
+    /// Deserialize an envelope from JSON
+    ///
+    /// The version is checked against [`ProtocolVersion::current`] before the
+    /// payload is decoded.
+    pub fn decode(json: &str) -> Result<Self, ProtocolError> {
+        Self::decode_with(json, &ProtocolVersion::current())
+    }
+
+    /// Deserialize an envelope, checking compatibility against `local`
+    pub fn decode_with(json: &str, local: &ProtocolVersion) -> Result<Self, ProtocolError> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| ProtocolError::DeserializationError { message: e.to_string() })?;
+
+        let version: ProtocolVersion = value
+            .get("version")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ProtocolError::DeserializationError {
+                message: "envelope is missing a version".into(),
+            })?
+            .parse()?;
+        local.check_compatible(&version)?;
+
+        serde_json::from_value(value)
+            .map_err(|e| ProtocolError::DeserializationError { message: e.to_string() })
+    }
+}
+
+/// Assigns monotonic sequence numbers to outgoing envelopes on one connection
+#[derive(Debug, Clone, Default)]
+pub struct Sequencer {
+    next: u64,
+}
+
+impl Sequencer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wrap a payload in an envelope carrying the next sequence number
+    pub fn wrap<T>(&mut self, payload: T) -> Envelope<T> {
+        let envelope = Envelope::new(self.next, payload);
+        self.next += 1;
+        envelope
+    }
+
+    /// The sequence number the next envelope will carry
+    pub fn peek(&self) -> u64 {
+        self.next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Event, Op, SubmissionId};
+
+    // === ProtocolVersion Tests ===
+
+    #[test]
+    fn test_current_version_matches_constant() {
+        assert_eq!(ProtocolVersion::current().to_string(), PROTOCOL_VERSION);
+    }
+
+    #[test]
+    fn test_version_parse() {
+        let v: ProtocolVersion = "1.2.3".parse().unwrap();
+        assert_eq!(v, ProtocolVersion::new(1, 2, 3));
+        assert!("1.2".parse::<ProtocolVersion>().is_err());
+        assert!("1.2.3.4".parse::<ProtocolVersion>().is_err());
+        assert!("one.two.three".parse::<ProtocolVersion>().is_err());
+    }
+
+    #[test]
+    fn test_version_compatibility() {
+        let v1 = ProtocolVersion::new(1, 0, 0);
+        assert!(v1.is_compatible_with(&ProtocolVersion::new(1, 4, 2)));
+        assert!(!v1.is_compatible_with(&ProtocolVersion::new(2, 0, 0)));
+    }
+
+    #[test]
+    fn test_version_serializes_as_string() {
+        let json = serde_json::to_string(&ProtocolVersion::new(0, 3, 1)).unwrap();
+        assert_eq!(json, "\"0.3.1\"");
+    }
+
+    // === Envelope Tests ===
+
+    #[test]
+    fn test_op_envelope_roundtrip() {
+        let envelope = Envelope::new(7, Op::user_input("hello"));
+        let json = envelope.encode().unwrap();
+        assert!(json.contains("\"seq\":7"));
+        assert!(json.contains("user_input"));
+
+        let parsed: Envelope<Op> = Envelope::decode(&json).unwrap();
+        assert_eq!(parsed.seq, 7);
+        assert_eq!(parsed.version, ProtocolVersion::current());
+        assert_eq!(parsed.payload.sub_id(), envelope.payload.sub_id());
+    }
+
+    #[test]
+    fn test_event_envelope_roundtrip() {
+        let envelope = Envelope::new(
+            0,
+            Event::Warning {
+                sub_id: SubmissionId::new(),
+                message: "careful".into(),
+                details: None,
+            },
+        );
+        let json = envelope.encode().unwrap();
+        let parsed: Envelope<Event> = Envelope::decode(&json).unwrap();
+        assert!(parsed.into_payload().requires_attention());
+    }
+
+    #[test]
+    fn test_decode_major_mismatch() {
+        let mut envelope = Envelope::new(0, Op::interrupt());
+        envelope.version = ProtocolVersion::new(99, 0, 0);
+        let json = envelope.encode().unwrap();
+
+        match Envelope::<Op>::decode(&json) {
+            Err(ProtocolError::VersionMismatch { expected, actual }) => {
+                assert_eq!(expected, PROTOCOL_VERSION);
+                assert_eq!(actual, "99.0.0");
+            }
+            other => panic!("Expected VersionMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_checks_version_before_payload() {
+        let json = r#"{
+            "version": "99.0.0",
+            "seq": 0,
+            "sent_at": "2024-01-01T00:00:00Z",
+            "payload": { "type": "from_the_future" }
+        }"#;
+        assert!(matches!(
+            Envelope::<Op>::decode(json),
+            Err(ProtocolError::VersionMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_missing_version() {
+        let json = r#"{
+            "seq": 0,
+            "sent_at": "2024-01-01T00:00:00Z",
+            "payload": { "type": "undo", "sub_id": "x" }
+        }"#;
+        assert!(matches!(
+            Envelope::<Op>::decode(json),
+            Err(ProtocolError::DeserializationError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_sequencer_is_monotonic() {
+        let mut seq = Sequencer::new();
+        let a = seq.wrap(Op::interrupt());
+        let b = seq.wrap(Op::interrupt());
+        let c = seq.wrap(Op::interrupt());
+        assert_eq!((a.seq, b.seq, c.seq), (0, 1, 2));
+        assert_eq!(seq.peek(), 3);
+    }
+}
@@ -13,6 +13,10 @@ pub enum ProtocolError {
     #[error("Failed to deserialize message: {message}")]
     DeserializationError { message: String },
 
+    /// Failed to encode message in a non-JSON format
+    #[error("Failed to encode message: {0}")]
+    EncodingError(String),
+
     /// Unknown operation type
     #[error("Unknown operation type: {0}")]
     UnknownOperation(String),
@@ -29,6 +33,22 @@ pub enum ProtocolError {
     #[error("Protocol version mismatch: expected {expected}, got {actual}")]
     VersionMismatch { expected: String, actual: String },
 
+    /// Wire format unknown or not compiled in
+    #[error("Unsupported wire format: {0}")]
+    UnsupportedFormat(String),
+
+    /// Frame exceeds the configured maximum size
+    #[error("Frame too large: {size} bytes exceeds limit of {max}")]
+    FrameTooLarge { size: usize, max: usize },
+
+    /// Hierarchy patch does not apply to the tree
+    #[error("Invalid hierarchy patch: {0}")]
+    InvalidPatch(String),
+
+    /// Network allowlist entry that cannot be parsed
+    #[error("Invalid allowlist entry {entry:?}: {reason}")]
+    InvalidAllowlistEntry { entry: String, reason: String },
+
     /// Transport error
     #[error("Transport error: {0}")]
     TransportError(String),
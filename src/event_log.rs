@@ -0,0 +1,373 @@
+//! Recording and replaying event streams
+//!
+//! An event log is newline-delimited JSON with one [`LoggedEvent`] per line:
+//! the `Event` plus the time it was received. [`EventLogWriter`] appends to
+//! it; [`EventLogReader`] replays it into any consumer, either instantly or
+//! paced by the recorded timestamps, and can skip ahead to the point where a
+//! checkpoint was saved or restored.
+//!
+//! ```
+//! use warhorn::event_log::{EventLogReader, EventLogWriter, ReplaySpeed};
+//! use warhorn::{Event, SubmissionId};
+//!
+//! let mut writer = EventLogWriter::new(Vec::new());
+//! writer.record(&Event::Warning {
+//!     sub_id: SubmissionId::new(),
+//!     message: "careful".into(),
+//!     details: None,
+//! }).unwrap();
+//!
+//! let bytes = writer.into_inner();
+//! let mut reader = EventLogReader::new(bytes.as_slice());
+//! let replayed = reader
+//!     .replay(ReplaySpeed::Instant, |logged| println!("{:?}", logged.event))
+//!     .unwrap();
+//! assert_eq!(replayed, 1);
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::codec::{NdjsonReader, NdjsonWriter};
+use crate::error::ProtocolError;
+use crate::events::Event;
+use crate::ids::CheckpointId;
+
+/// One line of an event log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    /// When the event was received
+    pub received_at: DateTime<Utc>,
+    pub event: Event,
+}
+
+impl LoggedEvent {
+    /// Check whether this event saved or restored `checkpoint_id`
+    ///
+    /// A `TurnComplete` that wrote the checkpoint counts as saving it.
+    pub fn touches_checkpoint(&self, checkpoint_id: &CheckpointId) -> bool {
+        match &self.event {
+            Event::CheckpointSaved { checkpoint_id: id, .. }
+            | Event::CheckpointRestored { checkpoint_id: id, .. }
+            | Event::TurnComplete { checkpoint_id: id, .. } => id == checkpoint_id,
+            _ => false,
+        }
+    }
+}
+
+/// Appends events to a log
+#[derive(Debug)]
+pub struct EventLogWriter<W> {
+    inner: NdjsonWriter<W, LoggedEvent>,
+}
+
+impl EventLogWriter<File> {
+    /// Open `path` for appending, creating it if needed
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, ProtocolError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| ProtocolError::TransportError(e.to_string()))?;
+        Ok(Self::new(file))
+    }
+}
+
+impl<W: Write> EventLogWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self { inner: NdjsonWriter::new(inner) }
+    }
+
+    /// Record an event received now
+    pub fn record(&mut self, event: &Event) -> Result<(), ProtocolError> {
+        self.record_at(event, Utc::now())
+    }
+
+    /// Record an event received at `received_at`
+    pub fn record_at(
+        &mut self,
+        event: &Event,
+        received_at: DateTime<Utc>,
+    ) -> Result<(), ProtocolError> {
+        self.inner.write(&LoggedEvent { received_at, event: event.clone() })
+    }
+
+    /// Unwrap the underlying writer
+    pub fn into_inner(self) -> W {
+        self.inner.into_inner()
+    }
+}
+
+/// How fast [`EventLogReader::replay`] delivers events
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// As fast as the consumer takes them
+    Instant,
+    /// With the gaps between events as recorded
+    Realtime,
+    /// With the recorded gaps divided by this factor (2.0 is twice as fast)
+    Multiplier(f64),
+}
+
+impl ReplaySpeed {
+    /// Time to wait for a recorded gap between two events
+    ///
+    /// Negative gaps, from clock adjustments, are treated as zero, as are
+    /// factors that are not positive. A factor so small that the delay
+    /// overflows waits `Duration::MAX`.
+    pub fn delay(&self, gap: chrono::Duration) -> Duration {
+        let gap = gap.to_std().unwrap_or_default();
+        match *self {
+            ReplaySpeed::Instant => Duration::ZERO,
+            ReplaySpeed::Realtime => gap,
+            ReplaySpeed::Multiplier(factor) if factor > 0.0 => {
+                Duration::try_from_secs_f64(gap.as_secs_f64() / factor).unwrap_or(Duration::MAX)
+            }
+            ReplaySpeed::Multiplier(_) => Duration::ZERO,
+        }
+    }
+}
+
+/// Reads events back from a log
+///
+/// Iterating yields one `Result` per line, like [`NdjsonReader`].
+#[derive(Debug)]
+pub struct EventLogReader<R> {
+    inner: NdjsonReader<R, LoggedEvent>,
+    last_received_at: Option<DateTime<Utc>>,
+}
+
+impl EventLogReader<File> {
+    /// Open the log at `path` from the beginning
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ProtocolError> {
+        let file = File::open(path).map_err(|e| ProtocolError::TransportError(e.to_string()))?;
+        Ok(Self::new(file))
+    }
+}
+
+impl<R: Read> EventLogReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner: NdjsonReader::new(inner),
+            last_received_at: None,
+        }
+    }
+
+    /// Skip ahead to the event that saved or restored `checkpoint_id`
+    ///
+    /// Returns that event, or `None` if the log ends first. Replay continues
+    /// with the event after it.
+    pub fn seek_to_checkpoint(
+        &mut self,
+        checkpoint_id: &CheckpointId,
+    ) -> Result<Option<LoggedEvent>, ProtocolError> {
+        for logged in self.by_ref() {
+            let logged = logged?;
+            if logged.touches_checkpoint(checkpoint_id) {
+                return Ok(Some(logged));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Deliver every remaining event to `consumer`, paced by `speed`
+    ///
+    /// Blocks the current thread while waiting between events. Stops at the
+    /// first malformed line and returns its error; otherwise returns the
+    /// number of events delivered.
+    pub fn replay(
+        &mut self,
+        speed: ReplaySpeed,
+        mut consumer: impl FnMut(LoggedEvent),
+    ) -> Result<usize, ProtocolError> {
+        let mut count = 0;
+        let mut previous = self.last_received_at;
+        for logged in self.by_ref() {
+            let logged = logged?;
+            if let Some(previous) = previous {
+                let delay = speed.delay(logged.received_at - previous);
+                if !delay.is_zero() {
+                    std::thread::sleep(delay);
+                }
+            }
+            previous = Some(logged.received_at);
+            consumer(logged);
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+impl<R: Read> Iterator for EventLogReader<R> {
+    type Item = Result<LoggedEvent, ProtocolError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let logged = self.inner.read()?;
+        if let Ok(logged) = &logged {
+            self.last_received_at = Some(logged.received_at);
+        }
+        Some(logged)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::all_events;
+    use crate::ids::*;
+    use chrono::TimeZone;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap() + chrono::Duration::seconds(seconds)
+    }
+
+    fn saved(checkpoint_id: CheckpointId) -> Event {
+        Event::CheckpointSaved {
+            sub_id: SubmissionId::new(),
+            checkpoint_id,
+            name: None,
+            timestamp: at(0),
+        }
+    }
+
+    fn warning(message: &str) -> Event {
+        Event::Warning { sub_id: SubmissionId::new(), message: message.into(), details: None }
+    }
+
+    // === Record and Replay Tests ===
+
+    #[test]
+    fn test_record_and_replay_every_event() {
+        let events = all_events();
+        let mut writer = EventLogWriter::new(Vec::new());
+        for (i, event) in events.iter().enumerate() {
+            writer.record_at(event, at(i as i64)).unwrap();
+        }
+        let bytes = writer.into_inner();
+
+        let mut replayed = Vec::new();
+        let count = EventLogReader::new(bytes.as_slice())
+            .replay(ReplaySpeed::Instant, |logged| replayed.push(logged))
+            .unwrap();
+
+        assert_eq!(count, events.len());
+        for (i, (logged, event)) in replayed.iter().zip(&events).enumerate() {
+            assert_eq!(logged.received_at, at(i as i64));
+            assert_eq!(logged.event.sub_id(), event.sub_id());
+        }
+    }
+
+    #[test]
+    fn test_replay_stops_at_malformed_line() {
+        let mut writer = EventLogWriter::new(Vec::new());
+        writer.record(&warning("one")).unwrap();
+        let mut bytes = writer.into_inner();
+        bytes.extend_from_slice(b"{truncated\n");
+
+        let mut seen = 0;
+        let mut reader = EventLogReader::new(bytes.as_slice());
+        let result = reader.replay(ReplaySpeed::Instant, |_| seen += 1);
+        assert!(matches!(result, Err(ProtocolError::DeserializationError { .. })));
+        assert_eq!(seen, 1);
+    }
+
+    #[test]
+    fn test_file_roundtrip_appends() {
+        let path = std::env::temp_dir().join(format!("warhorn-log-{}.jsonl", SubmissionId::new()));
+        for message in ["first", "second"] {
+            EventLogWriter::create(&path).unwrap().record(&warning(message)).unwrap();
+        }
+
+        let events: Vec<LoggedEvent> = EventLogReader::open(&path)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(events.len(), 2);
+    }
+
+    // === Seek Tests ===
+
+    #[test]
+    fn test_seek_to_checkpoint() {
+        let wanted = CheckpointId::new();
+        let mut writer = EventLogWriter::new(Vec::new());
+        writer.record_at(&warning("before"), at(0)).unwrap();
+        writer.record_at(&saved(CheckpointId::new()), at(1)).unwrap();
+        writer.record_at(&saved(wanted), at(2)).unwrap();
+        writer.record_at(&warning("after"), at(3)).unwrap();
+        let bytes = writer.into_inner();
+
+        let mut reader = EventLogReader::new(bytes.as_slice());
+        let found = reader.seek_to_checkpoint(&wanted).unwrap().unwrap();
+        assert_eq!(found.received_at, at(2));
+
+        let rest: Vec<LoggedEvent> = reader.collect::<Result<_, _>>().unwrap();
+        assert_eq!(rest.len(), 1);
+        assert!(matches!(&rest[0].event, Event::Warning { message, .. } if message == "after"));
+    }
+
+    #[test]
+    fn test_seek_to_missing_checkpoint() {
+        let mut writer = EventLogWriter::new(Vec::new());
+        writer.record(&warning("only")).unwrap();
+        let bytes = writer.into_inner();
+
+        let mut reader = EventLogReader::new(bytes.as_slice());
+        assert!(reader.seek_to_checkpoint(&CheckpointId::new()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_turn_complete_touches_checkpoint() {
+        let checkpoint_id = CheckpointId::new();
+        let logged = LoggedEvent {
+            received_at: at(0),
+            event: Event::TurnComplete {
+                sub_id: SubmissionId::new(),
+                task_id: TaskId::new(),
+                turn_number: 1,
+                checkpoint_id,
+            },
+        };
+        assert!(logged.touches_checkpoint(&checkpoint_id));
+        assert!(!logged.touches_checkpoint(&CheckpointId::new()));
+    }
+
+    // === Speed Tests ===
+
+    #[test]
+    fn test_replay_speed_delay() {
+        let gap = chrono::Duration::milliseconds(1000);
+        assert_eq!(ReplaySpeed::Instant.delay(gap), Duration::ZERO);
+        assert_eq!(ReplaySpeed::Realtime.delay(gap), Duration::from_secs(1));
+        assert_eq!(ReplaySpeed::Multiplier(4.0).delay(gap), Duration::from_millis(250));
+        assert_eq!(ReplaySpeed::Multiplier(0.0).delay(gap), Duration::ZERO);
+        assert_eq!(ReplaySpeed::Realtime.delay(-gap), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_replay_speed_extreme_factors() {
+        let gap = chrono::Duration::seconds(1);
+        assert_eq!(ReplaySpeed::Multiplier(1e-300).delay(gap), Duration::MAX);
+        assert_eq!(ReplaySpeed::Multiplier(f64::NAN).delay(gap), Duration::ZERO);
+        assert_eq!(ReplaySpeed::Multiplier(f64::INFINITY).delay(gap), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_replay_paces_events() {
+        let mut writer = EventLogWriter::new(Vec::new());
+        writer.record_at(&warning("a"), at(0)).unwrap();
+        writer.record_at(&warning("b"), at(1)).unwrap();
+        let bytes = writer.into_inner();
+
+        let start = std::time::Instant::now();
+        EventLogReader::new(bytes.as_slice())
+            .replay(ReplaySpeed::Multiplier(20.0), |_| {})
+            .unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -5,6 +5,7 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
+use crate::envelope::ProtocolVersion;
 use crate::ids::*;
 use crate::models::*;
 
@@ -12,11 +13,23 @@ use crate::models::*;
 ///
 /// Each event includes a `sub_id` correlating to the operation that triggered it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[non_exhaustive]
 pub enum Event {
     // === Session Events ===
 
+    /// Reply to `Op::Hello` completing the handshake
+    Welcome {
+        sub_id: SubmissionId,
+        session_id: SessionId,
+        /// Highest protocol version the server speaks
+        server_version: ProtocolVersion,
+        /// Capabilities enabled for this connection
+        #[serde(default)]
+        accepted_capabilities: Vec<Capability>,
+    },
+
     /// Session has been configured/reconfigured
     SessionConfigured {
         sub_id: SubmissionId,
@@ -169,6 +182,15 @@ pub enum Event {
         root: AgentTree,
     },
 
+    /// Incremental changes to the agent hierarchy
+    ///
+    /// Sent instead of `HierarchyUpdated` when `Capability::HierarchyPatches`
+    /// was negotiated; apply with `AgentTree::apply`.
+    HierarchyPatch {
+        sub_id: SubmissionId,
+        ops: Vec<HierarchyOp>,
+    },
+
     // === Checkpoint Events ===
 
     /// Checkpoint saved
@@ -206,6 +228,62 @@ pub enum Event {
         plan: TaskPlan,
     },
 
+    /// Plan replaced by a revision, from `RevisePlan` or replanning
+    PlanRevised {
+        sub_id: SubmissionId,
+        plan: TaskPlan,
+        /// Changes from the previous plan
+        diff: PlanDiff,
+    },
+
+    /// Plan approved by `ApprovePlan`; execution follows
+    PlanApproved {
+        sub_id: SubmissionId,
+    },
+
+    /// Agent began working on a plan step
+    PlanStepStarted {
+        sub_id: SubmissionId,
+        /// `PlanStep.id` of the step
+        step_id: String,
+        agent_id: AgentId,
+        task_id: TaskId,
+    },
+
+    /// Plan step finished successfully
+    PlanStepCompleted {
+        sub_id: SubmissionId,
+        /// `PlanStep.id` of the step
+        step_id: String,
+        agent_id: AgentId,
+        task_id: TaskId,
+    },
+
+    /// Plan step failed
+    PlanStepFailed {
+        sub_id: SubmissionId,
+        /// `PlanStep.id` of the step
+        step_id: String,
+        agent_id: AgentId,
+        task_id: TaskId,
+        error: String,
+    },
+
+    /// Plan step will not run
+    PlanStepSkipped {
+        sub_id: SubmissionId,
+        /// `PlanStep.id` of the step
+        step_id: String,
+        /// Agent the step was assigned to, if it got that far
+        #[serde(default)]
+        agent_id: Option<AgentId>,
+        /// Task created for the step, if it got that far
+        #[serde(default)]
+        task_id: Option<TaskId>,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+
     // === System Events ===
 
     /// Non-fatal warning
@@ -228,14 +306,41 @@ pub enum Event {
     UsageUpdate {
         sub_id: SubmissionId,
         agent_id: Option<AgentId>,
+        /// Model that consumed the tokens, if the producer knows it
+        #[serde(default)]
+        model: Option<String>,
+        /// Task the tokens were spent on, if the producer knows it
+        #[serde(default)]
+        task_id: Option<TaskId>,
         usage: TokenUsage,
     },
+
+    /// Token use crossed a warning threshold of a budget
+    BudgetWarning {
+        sub_id: SubmissionId,
+        /// Agent whose budget it is; `None` for the session budget
+        agent_id: Option<AgentId>,
+        used: u64,
+        budget: u64,
+        /// Percentage of the budget that was reached
+        threshold: u8,
+    },
+
+    /// Token use went over a budget
+    BudgetExceeded {
+        sub_id: SubmissionId,
+        /// Agent whose budget it is; `None` for the session budget
+        agent_id: Option<AgentId>,
+        used: u64,
+        budget: u64,
+    },
 }
 
 impl Event {
     /// Get the submission ID for this event
     pub fn sub_id(&self) -> &SubmissionId {
         match self {
+            Event::Welcome { sub_id, .. } => sub_id,
             Event::SessionConfigured { sub_id, .. } => sub_id,
             Event::SettingsUpdated { sub_id, .. } => sub_id,
             Event::TaskStarted { sub_id, .. } => sub_id,
@@ -254,14 +359,23 @@ impl Event {
             Event::ToolCallComplete { sub_id, .. } => sub_id,
             Event::ToolCallFailed { sub_id, .. } => sub_id,
             Event::HierarchyUpdated { sub_id, .. } => sub_id,
+            Event::HierarchyPatch { sub_id, .. } => sub_id,
             Event::CheckpointSaved { sub_id, .. } => sub_id,
             Event::CheckpointRestored { sub_id, .. } => sub_id,
             Event::CheckpointList { sub_id, .. } => sub_id,
             Event::PlanModeChanged { sub_id, .. } => sub_id,
             Event::PlanCreated { sub_id, .. } => sub_id,
+            Event::PlanRevised { sub_id, .. } => sub_id,
+            Event::PlanApproved { sub_id } => sub_id,
+            Event::PlanStepStarted { sub_id, .. } => sub_id,
+            Event::PlanStepCompleted { sub_id, .. } => sub_id,
+            Event::PlanStepFailed { sub_id, .. } => sub_id,
+            Event::PlanStepSkipped { sub_id, .. } => sub_id,
             Event::Warning { sub_id, .. } => sub_id,
             Event::Error { sub_id, .. } => sub_id,
             Event::UsageUpdate { sub_id, .. } => sub_id,
+            Event::BudgetWarning { sub_id, .. } => sub_id,
+            Event::BudgetExceeded { sub_id, .. } => sub_id,
         }
     }
 
@@ -274,7 +388,11 @@ impl Event {
     pub fn requires_attention(&self) -> bool {
         matches!(
             self,
-            Event::ApprovalRequired { .. } | Event::Error { .. } | Event::Warning { .. }
+            Event::ApprovalRequired { .. }
+                | Event::Error { .. }
+                | Event::Warning { .. }
+                | Event::BudgetWarning { .. }
+                | Event::BudgetExceeded { .. }
         )
     }
 }
@@ -537,6 +655,21 @@ mod tests {
 
     // === Session Event Tests ===
 
+    #[test]
+    fn test_welcome_event() {
+        let event = Event::Welcome {
+            sub_id: SubmissionId::new(),
+            session_id: SessionId::new(),
+            server_version: ProtocolVersion::current(),
+            accepted_capabilities: vec![Capability::HierarchyPatches],
+        };
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains("welcome"));
+        assert!(json.contains("hierarchy_patches"));
+        assert!(json.contains(crate::PROTOCOL_VERSION));
+    }
+
     #[test]
     fn test_session_configured_event() {
         let event = Event::SessionConfigured {
@@ -635,6 +768,7 @@ mod tests {
                         description: "Create auth module".into(),
                         expected_outcome: "Auth module created".into(),
                         complexity: StepComplexity::Moderate,
+                        estimated_tokens: None,
                     },
                 ],
                 agent_assignments: Default::default(),
@@ -648,6 +782,50 @@ mod tests {
         assert!(json.contains("Add auth"));
     }
 
+    #[test]
+    fn test_plan_revised_event() {
+        let event = Event::PlanRevised {
+            sub_id: SubmissionId::new(),
+            plan: TaskPlan {
+                original_request: "Add auth".into(),
+                steps: vec![],
+                agent_assignments: Default::default(),
+                dependencies: vec![],
+                estimated_tokens: 0,
+            },
+            diff: PlanDiff::default(),
+        };
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains(r#""type":"plan_revised""#));
+        assert!(json.contains(r#""diff":{"added":[],"removed":[],"changed":[]}"#));
+    }
+
+    #[test]
+    fn test_plan_step_events() {
+        let event = Event::PlanStepFailed {
+            sub_id: SubmissionId::new(),
+            step_id: "2".into(),
+            agent_id: AgentId::new(),
+            task_id: TaskId::new(),
+            error: "tests fail".into(),
+        };
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains(r#""type":"plan_step_failed""#));
+        assert!(json.contains(r#""step_id":"2""#));
+
+        let json = r#"{
+            "type": "plan_step_skipped",
+            "sub_id": "00000000-0000-0000-0000-000000000000",
+            "step_id": "3"
+        }"#;
+        let parsed: Event = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            parsed,
+            Event::PlanStepSkipped { agent_id: None, task_id: None, reason: None, .. }
+        ));
+    }
+
     // === System Event Tests ===
 
     #[test]
@@ -685,11 +863,14 @@ mod tests {
         let event = Event::UsageUpdate {
             sub_id: SubmissionId::new(),
             agent_id: Some(AgentId::new()),
+            model: None,
+            task_id: None,
             usage: TokenUsage {
                 input_tokens: 1000,
                 output_tokens: 500,
                 total_tokens: 1500,
                 estimated_cost_usd: Some(0.015),
+                ..Default::default()
             },
         };
         
@@ -698,6 +879,21 @@ mod tests {
         assert!(json.contains("1500"));
     }
 
+    #[test]
+    fn test_budget_events() {
+        let event = Event::BudgetExceeded {
+            sub_id: SubmissionId::new(),
+            agent_id: None,
+            used: 1_001,
+            budget: 1_000,
+        };
+        assert!(event.requires_attention());
+
+        let json = serde_json::to_string(&event).unwrap();
+        assert!(json.contains(r#""type":"budget_exceeded""#));
+        assert!(json.contains(r#""agent_id":null"#));
+    }
+
     // === Hierarchy Event Tests ===
 
     #[test]
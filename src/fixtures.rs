@@ -0,0 +1,403 @@
+//! Sample values covering every `Op` and `Event` variant, shared by tests
+
+use chrono::{TimeZone, Utc};
+use std::path::PathBuf;
+
+use crate::envelope::ProtocolVersion;
+use crate::events::Event;
+use crate::ids::*;
+use crate::models::*;
+use crate::ops::Op;
+
+pub fn sample_tree() -> AgentTree {
+    AgentTree {
+        agent_id: AgentId::new(),
+        role: AgentRole::Orchestrator,
+        status: AgentStatus::Running,
+        task_summary: Some("Managing".into()),
+        children: vec![
+            AgentTree {
+                agent_id: AgentId::new(),
+                role: AgentRole::DomainLead { domain: "backend".into() },
+                status: AgentStatus::Waiting { reason: "approval".into() },
+                task_summary: None,
+                children: vec![],
+            },
+            AgentTree {
+                agent_id: AgentId::new(),
+                role: AgentRole::Worker,
+                status: AgentStatus::Completed,
+                task_summary: Some("Coding".into()),
+                children: vec![],
+            },
+        ],
+    }
+}
+
+pub fn sample_plan() -> TaskPlan {
+    TaskPlan {
+        original_request: "Add auth".into(),
+        steps: vec![
+            PlanStep {
+                id: "1".into(),
+                description: "Create auth module".into(),
+                expected_outcome: "Module exists".into(),
+                complexity: StepComplexity::Complex,
+                estimated_tokens: Some(9_000),
+            },
+            PlanStep {
+                id: "2".into(),
+                description: "Add tests".into(),
+                expected_outcome: "Tests pass".into(),
+                complexity: StepComplexity::Simple,
+                estimated_tokens: Some(3_000),
+            },
+        ],
+        agent_assignments: [("1".into(), AgentRole::Specialist { specialty: "security".into() })]
+            .into(),
+        dependencies: vec![("1".into(), "2".into())],
+        estimated_tokens: 12_000,
+    }
+}
+
+pub fn sample_usage() -> TokenUsage {
+    TokenUsage {
+        input_tokens: 1200,
+        output_tokens: 300,
+        cache_read_tokens: 4000,
+        cache_write_tokens: 800,
+        total_tokens: 1500,
+        estimated_cost_usd: Some(0.0125),
+    }
+}
+
+pub fn all_ops() -> Vec<Op> {
+    let sub_id = SubmissionId::new;
+    vec![
+        Op::Hello {
+            sub_id: sub_id(),
+            client_name: "lair".into(),
+            protocol_version: ProtocolVersion::current(),
+            capabilities: vec![Capability::StreamingDeltas, Capability::BlobAttachments],
+        },
+        Op::ConfigureSession {
+            sub_id: sub_id(),
+            config: SessionConfig {
+                cwd: Some(PathBuf::from("/project")),
+                model: Some("model-x".into()),
+                mcp_servers: vec![McpServerConfig {
+                    id: "fs".into(),
+                    name: "Filesystem".into(),
+                    transport: McpTransport::Stdio {
+                        command: "mcp-fs".into(),
+                        args: vec!["--root".into(), "/".into()],
+                    },
+                    env: [("KEY".into(), "value".into())].into(),
+                }],
+                sandbox: SandboxConfig {
+                    enabled: true,
+                    network: NetworkPolicy::Allowlist(vec!["api.example.com".into()]),
+                    writable_paths: vec![PathBuf::from("/tmp")],
+                    filesystem: FilesystemPolicy {
+                        readable_roots: vec![
+                            PathBuf::from("/project"),
+                            PathBuf::from("/usr/share"),
+                        ],
+                        deny: vec!["~/.ssh".into(), "**/secrets/**".into()],
+                        ..Default::default()
+                    },
+                    timeout_secs: Some(30),
+                },
+                ..Default::default()
+            },
+        },
+        Op::UserInput {
+            sub_id: sub_id(),
+            prompt: "Add authentication".into(),
+            images: vec![ImageAttachment {
+                data: "iVBORw0KGgo=".into(),
+                mime_type: "image/png".into(),
+                filename: None,
+            }],
+            context: TaskContext {
+                cwd: None,
+                files: vec![PathBuf::from("src/lib.rs")],
+                memory_context: vec!["remember".into()],
+                metadata: [("nested".into(), serde_json::json!({"a": [1, 2.5, null, true]}))]
+                    .into(),
+            },
+            checkpoint_id: Some(CheckpointId::new()),
+        },
+        Op::Interrupt { sub_id: sub_id(), task_id: Some(TaskId::new()) },
+        Op::ExecApproval {
+            sub_id: sub_id(),
+            call_id: CallId::new(),
+            approved: true,
+            modified_command: Some("ls -la".into()),
+        },
+        Op::McpApproval { sub_id: sub_id(), call_id: CallId::new(), approved: false },
+        Op::SpawnAgent {
+            sub_id: sub_id(),
+            config: AgentConfig {
+                role: AgentRole::Custom { name: "janitor".into() },
+                can_spawn: true,
+                max_children: Some(3),
+                token_budget: Some(50_000),
+                ..Default::default()
+            },
+            parent_id: Some(AgentId::new()),
+            task: TaskAssignment {
+                task_id: TaskId::new(),
+                description: "Clean up".into(),
+                deliverables: vec!["diff".into()],
+                dependencies: vec![TaskId::new()],
+                context: TaskContext::default(),
+            },
+        },
+        Op::TerminateAgent { sub_id: sub_id(), agent_id: AgentId::new(), reason: None },
+        Op::RouteMessage { sub_id: sub_id(), agent_id: AgentId::new(), content: "hi".into() },
+        Op::SaveCheckpoint { sub_id: sub_id(), name: Some("before".into()) },
+        Op::RestoreCheckpoint { sub_id: sub_id(), checkpoint_id: CheckpointId::new() },
+        Op::ListCheckpoints { sub_id: sub_id() },
+        Op::Undo { sub_id: sub_id() },
+        Op::TogglePlanMode {
+            sub_id: sub_id(),
+            enabled: true,
+            granularity: PlanGranularity::Coarse,
+        },
+        Op::ApprovePlan { sub_id: sub_id() },
+        Op::RejectPlan { sub_id: sub_id(), feedback: Some("Split step 1".into()) },
+        Op::RevisePlan { sub_id: sub_id(), plan: sample_plan() },
+        Op::UpdateSettings {
+            sub_id: sub_id(),
+            settings: SessionSettings {
+                show_rate_limit: true,
+                subagent_concurrency: Some(2),
+                plan_granularity: PlanGranularity::Detailed,
+            },
+        },
+    ]
+}
+
+pub fn all_events() -> Vec<Event> {
+    let sub_id = SubmissionId::new;
+    let timestamp = Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap();
+    vec![
+        Event::Welcome {
+            sub_id: sub_id(),
+            session_id: SessionId::new(),
+            server_version: ProtocolVersion::current(),
+            accepted_capabilities: vec![Capability::HierarchyPatches],
+        },
+        Event::SessionConfigured {
+            sub_id: sub_id(),
+            session_id: SessionId::new(),
+            config: SessionConfig::default(),
+        },
+        Event::SettingsUpdated { sub_id: sub_id(), settings: SessionSettings::default() },
+        Event::TaskStarted { sub_id: sub_id(), task_id: TaskId::new(), prompt: "go".into() },
+        Event::TurnComplete {
+            sub_id: sub_id(),
+            task_id: TaskId::new(),
+            turn_number: 3,
+            checkpoint_id: CheckpointId::new(),
+        },
+        Event::TaskComplete {
+            sub_id: sub_id(),
+            task_id: TaskId::new(),
+            result: TaskResult {
+                task_id: TaskId::new(),
+                success: true,
+                summary: "done".into(),
+                files_changed: vec![PathBuf::from("a.rs")],
+                token_usage: sample_usage(),
+            },
+        },
+        Event::TaskFailed { sub_id: sub_id(), task_id: TaskId::new(), error: "boom".into() },
+        Event::TaskInterrupted { sub_id: sub_id(), task_id: TaskId::new() },
+        Event::AgentSpawned {
+            sub_id: sub_id(),
+            agent_id: AgentId::new(),
+            parent_id: None,
+            role: AgentRole::Scout,
+            config: AgentConfig::default(),
+        },
+        Event::AgentWorking {
+            sub_id: sub_id(),
+            agent_id: AgentId::new(),
+            task_summary: "Reading".into(),
+        },
+        Event::AgentStatusChanged {
+            sub_id: sub_id(),
+            agent_id: AgentId::new(),
+            status: AgentStatus::Waiting { reason: "dependency".into() },
+        },
+        Event::AgentMessage {
+            sub_id: sub_id(),
+            agent_id: AgentId::new(),
+            content: "thinking…".into(),
+            streaming: true,
+            message_type: MessageType::Thinking,
+        },
+        Event::AgentComplete {
+            sub_id: sub_id(),
+            agent_id: AgentId::new(),
+            result: AgentResult {
+                success: true,
+                summary: "ok".into(),
+                files_changed: vec![],
+                output: serde_json::json!({"lines": 42, "ratio": -0.5}),
+            },
+        },
+        Event::AgentTerminated {
+            sub_id: sub_id(),
+            agent_id: AgentId::new(),
+            reason: "user".into(),
+        },
+        Event::ToolCallStart {
+            sub_id: sub_id(),
+            agent_id: AgentId::new(),
+            call_id: CallId::new(),
+            tool_name: "shell".into(),
+            arguments: serde_json::json!({"command": "ls", "args": ["-la"]}),
+        },
+        Event::ApprovalRequired {
+            sub_id: sub_id(),
+            agent_id: AgentId::new(),
+            call_id: CallId::new(),
+            tool_name: "shell".into(),
+            arguments: serde_json::json!({"command": "rm -rf build"}),
+            description: "Delete build".into(),
+            risk: RiskLevel::High,
+        },
+        Event::ToolCallComplete {
+            sub_id: sub_id(),
+            agent_id: AgentId::new(),
+            call_id: CallId::new(),
+            tool_name: "read_file".into(),
+            output: ToolOutput {
+                success: true,
+                content: "contents".into(),
+                data: Some(serde_json::json!({"bytes": 8})),
+                exit_code: Some(-1),
+            },
+            duration_ms: 12,
+        },
+        Event::ToolCallFailed {
+            sub_id: sub_id(),
+            agent_id: AgentId::new(),
+            call_id: CallId::new(),
+            tool_name: "shell".into(),
+            error: "not found".into(),
+        },
+        Event::HierarchyUpdated { sub_id: sub_id(), root: sample_tree() },
+        Event::HierarchyPatch {
+            sub_id: sub_id(),
+            ops: vec![
+                HierarchyOp::InsertChild {
+                    parent_id: AgentId::new(),
+                    index: 0,
+                    node: sample_tree(),
+                },
+                HierarchyOp::UpdateStatus {
+                    agent_id: AgentId::new(),
+                    status: AgentStatus::Waiting { reason: "review".into() },
+                },
+                HierarchyOp::UpdateTaskSummary { agent_id: AgentId::new(), task_summary: None },
+                HierarchyOp::RemoveSubtree { agent_id: AgentId::new() },
+            ],
+        },
+        Event::CheckpointSaved {
+            sub_id: sub_id(),
+            checkpoint_id: CheckpointId::new(),
+            name: None,
+            timestamp,
+        },
+        Event::CheckpointRestored { sub_id: sub_id(), checkpoint_id: CheckpointId::new() },
+        Event::CheckpointList {
+            sub_id: sub_id(),
+            checkpoints: vec![CheckpointMeta {
+                id: CheckpointId::new(),
+                name: Some("cp".into()),
+                timestamp,
+                size_bytes: u64::MAX,
+                task_id: Some(TaskId::new()),
+                summary: "snapshot".into(),
+            }],
+        },
+        Event::PlanModeChanged {
+            sub_id: sub_id(),
+            enabled: false,
+            granularity: PlanGranularity::Auto,
+        },
+        Event::PlanCreated { sub_id: sub_id(), plan: sample_plan() },
+        Event::PlanRevised {
+            sub_id: sub_id(),
+            plan: sample_plan(),
+            diff: PlanDiff {
+                added: vec![sample_plan().steps[1].clone()],
+                removed: vec![],
+                changed: vec![PlanStepChange {
+                    before: PlanStep {
+                        description: "Sketch auth module".into(),
+                        ..sample_plan().steps[0].clone()
+                    },
+                    after: sample_plan().steps[0].clone(),
+                }],
+            },
+        },
+        Event::PlanApproved { sub_id: sub_id() },
+        Event::PlanStepStarted {
+            sub_id: sub_id(),
+            step_id: "1".into(),
+            agent_id: AgentId::new(),
+            task_id: TaskId::new(),
+        },
+        Event::PlanStepCompleted {
+            sub_id: sub_id(),
+            step_id: "1".into(),
+            agent_id: AgentId::new(),
+            task_id: TaskId::new(),
+        },
+        Event::PlanStepFailed {
+            sub_id: sub_id(),
+            step_id: "2".into(),
+            agent_id: AgentId::new(),
+            task_id: TaskId::new(),
+            error: "tests fail".into(),
+        },
+        Event::PlanStepSkipped {
+            sub_id: sub_id(),
+            step_id: "3".into(),
+            agent_id: None,
+            task_id: None,
+            reason: Some("dependency failed".into()),
+        },
+        Event::Warning {
+            sub_id: sub_id(),
+            message: "careful".into(),
+            details: Some("details".into()),
+        },
+        Event::Error { sub_id: sub_id(), message: "bad".into(), recoverable: true },
+        Event::UsageUpdate {
+            sub_id: sub_id(),
+            agent_id: Some(AgentId::new()),
+            model: Some("model-x".into()),
+            task_id: Some(TaskId::new()),
+            usage: sample_usage(),
+        },
+        Event::BudgetWarning {
+            sub_id: sub_id(),
+            agent_id: Some(AgentId::new()),
+            used: 80_000,
+            budget: 100_000,
+            threshold: 80,
+        },
+        Event::BudgetExceeded {
+            sub_id: sub_id(),
+            agent_id: None,
+            used: 1_000_001,
+            budget: 1_000_000,
+        },
+    ]
+}
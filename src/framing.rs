@@ -0,0 +1,416 @@
+//! Length-prefixed binary framing
+//!
+//! Each frame on the wire is laid out as:
+//!
+//! ```text
+//! ┌────────────────┬─────────────┬──────────────────┐
+//! │ length (u32 BE)│ format (u8) │ payload (length) │
+//! └────────────────┴─────────────┴──────────────────┘
+//! ```
+//!
+//! `length` counts payload bytes only. JSON is always available; CBOR and
+//! MessagePack are enabled with the `cbor` and `msgpack` features. Readers
+//! accept any compiled-in format regardless of what they write.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+use crate::codec::DEFAULT_MAX_FRAME_SIZE;
+use crate::error::ProtocolError;
+
+/// Size of the frame header in bytes
+pub const HEADER_LEN: usize = 5;
+
+/// Serialization format of a frame payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Format {
+    /// JSON text
+    #[default]
+    Json,
+    /// CBOR (requires the `cbor` feature)
+    Cbor,
+    /// MessagePack (requires the `msgpack` feature)
+    MessagePack,
+}
+
+impl Format {
+    /// Wire value of this format
+    pub fn as_byte(self) -> u8 {
+        match self {
+            Format::Json => 0,
+            Format::Cbor => 1,
+            Format::MessagePack => 2,
+        }
+    }
+
+    /// Parse a wire format byte
+    pub fn from_byte(byte: u8) -> Result<Self, ProtocolError> {
+        match byte {
+            0 => Ok(Format::Json),
+            1 => Ok(Format::Cbor),
+            2 => Ok(Format::MessagePack),
+            other => Err(ProtocolError::UnsupportedFormat(format!("unknown format byte {other}"))),
+        }
+    }
+
+    /// Check whether this format was compiled in
+    pub fn is_available(self) -> bool {
+        match self {
+            Format::Json => true,
+            Format::Cbor => cfg!(feature = "cbor"),
+            Format::MessagePack => cfg!(feature = "msgpack"),
+        }
+    }
+
+    /// Serialize a message in this format
+    pub fn serialize<T: Serialize>(self, message: &T) -> Result<Vec<u8>, ProtocolError> {
+        match self {
+            Format::Json => Ok(serde_json::to_vec(message)?),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => {
+                let mut buf = Vec::new();
+                ciborium::into_writer(message, &mut buf)
+                    .map_err(|e| ProtocolError::EncodingError(e.to_string()))?;
+                Ok(buf)
+            }
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::to_vec_named(message)
+                .map_err(|e| ProtocolError::EncodingError(e.to_string())),
+            #[allow(unreachable_patterns)]
+            other => Err(other.unavailable()),
+        }
+    }
+
+    /// Deserialize a message in this format
+    pub fn deserialize<T: DeserializeOwned>(self, payload: &[u8]) -> Result<T, ProtocolError> {
+        let result = match self {
+            Format::Json => serde_json::from_slice(payload).map_err(|e| e.to_string()),
+            #[cfg(feature = "cbor")]
+            Format::Cbor => ciborium::from_reader(payload).map_err(|e| e.to_string()),
+            #[cfg(feature = "msgpack")]
+            Format::MessagePack => rmp_serde::from_slice(payload).map_err(|e| e.to_string()),
+            #[allow(unreachable_patterns)]
+            other => return Err(other.unavailable()),
+        };
+        result.map_err(|message| ProtocolError::DeserializationError { message })
+    }
+
+    #[allow(dead_code)]
+    fn unavailable(self) -> ProtocolError {
+        ProtocolError::UnsupportedFormat(format!("{:?} support is not compiled in", self))
+    }
+}
+
+/// Encode a message as a complete frame
+pub fn encode_frame<T: Serialize>(message: &T, format: Format) -> Result<Vec<u8>, ProtocolError> {
+    let payload = format.serialize(message)?;
+    let len = u32::try_from(payload.len()).map_err(|_| ProtocolError::FrameTooLarge {
+        size: payload.len(),
+        max: u32::MAX as usize,
+    })?;
+
+    let mut frame = Vec::with_capacity(HEADER_LEN + payload.len());
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.push(format.as_byte());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+/// Decode a complete frame produced by [`encode_frame`]
+pub fn decode_frame<T: DeserializeOwned>(frame: &[u8]) -> Result<T, ProtocolError> {
+    let truncated = || ProtocolError::DeserializationError {
+        message: "truncated frame".into(),
+    };
+    let header = frame.get(..HEADER_LEN).ok_or_else(truncated)?;
+    let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let format = Format::from_byte(header[4])?;
+    let payload = frame.get(HEADER_LEN..HEADER_LEN + len).ok_or_else(truncated)?;
+    format.deserialize(payload)
+}
+
+/// Writes length-prefixed frames
+#[derive(Debug)]
+pub struct FrameWriter<W, T> {
+    inner: W,
+    format: Format,
+    max_frame_size: usize,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<W: Write, T: Serialize> FrameWriter<W, T> {
+    pub fn new(inner: W, format: Format) -> Self {
+        Self {
+            inner,
+            format,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the maximum payload size of a frame
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// Format used for outgoing frames
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Encode and write one frame, then flush
+    pub fn write(&mut self, message: &T) -> Result<(), ProtocolError> {
+        let frame = encode_frame(message, self.format)?;
+        let size = frame.len() - HEADER_LEN;
+        if size > self.max_frame_size {
+            return Err(ProtocolError::FrameTooLarge { size, max: self.max_frame_size });
+        }
+
+        self.inner.write_all(&frame).map_err(io_error)?;
+        self.inner.flush().map_err(io_error)
+    }
+
+    /// Unwrap the underlying writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Reads length-prefixed frames
+///
+/// As with [`NdjsonReader`](crate::codec::NdjsonReader), a frame that fails
+/// to decode or exceeds the size limit is reported and skipped, while an I/O
+/// error, including a stream cut off mid-frame, is reported once and ends
+/// the stream.
+#[derive(Debug)]
+pub struct FrameReader<R, T> {
+    inner: R,
+    max_frame_size: usize,
+    buf: Vec<u8>,
+    /// Set after an I/O error, since the stream is no longer at a frame boundary
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T: DeserializeOwned> FrameReader<R, T> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            buf: Vec::new(),
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the maximum payload size of a frame
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// Read the next frame
+    ///
+    /// Returns `None` on a clean EOF between frames and after an I/O error.
+    pub fn read(&mut self) -> Option<Result<T, ProtocolError>> {
+        if self.done {
+            return None;
+        }
+        let mut header = [0u8; HEADER_LEN];
+        match read_header(&mut self.inner, &mut header) {
+            Ok(true) => {}
+            Ok(false) => return None,
+            Err(e) => return self.fail(e),
+        }
+
+        let size = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        if size > self.max_frame_size {
+            return match skip(&mut self.inner, size) {
+                Ok(()) => {
+                    Some(Err(ProtocolError::FrameTooLarge { size, max: self.max_frame_size }))
+                }
+                Err(e) => self.fail(e),
+            };
+        }
+
+        self.buf.resize(size, 0);
+        if let Err(e) = self.inner.read_exact(&mut self.buf) {
+            return self.fail(e);
+        }
+
+        Some(Format::from_byte(header[4]).and_then(|format| format.deserialize(&self.buf)))
+    }
+
+    fn fail(&mut self, e: io::Error) -> Option<Result<T, ProtocolError>> {
+        self.done = true;
+        Some(Err(io_error(e)))
+    }
+}
+
+impl<R: Read, T: DeserializeOwned> Iterator for FrameReader<R, T> {
+    type Item = Result<T, ProtocolError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read()
+    }
+}
+
+/// Fill `header`, returning `false` on EOF before the first byte
+fn read_header<R: Read>(reader: &mut R, header: &mut [u8; HEADER_LEN]) -> io::Result<bool> {
+    let mut filled = 0;
+    while filled < HEADER_LEN {
+        match reader.read(&mut header[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(true)
+}
+
+fn skip<R: Read>(reader: &mut R, len: usize) -> io::Result<()> {
+    let copied = io::copy(&mut reader.take(len as u64), &mut io::sink())?;
+    if copied < len as u64 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(())
+}
+
+fn io_error(e: io::Error) -> ProtocolError {
+    ProtocolError::TransportError(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{all_events, all_ops};
+    use crate::{Event, Op};
+
+    fn available_formats() -> Vec<Format> {
+        [Format::Json, Format::Cbor, Format::MessagePack]
+            .into_iter()
+            .filter(|f| f.is_available())
+            .collect()
+    }
+
+    fn assert_roundtrip<T: Serialize + DeserializeOwned>(messages: &[T], format: Format) {
+        for message in messages {
+            let frame = encode_frame(message, format).unwrap();
+            let parsed: T = decode_frame(&frame).unwrap();
+            assert_eq!(
+                serde_json::to_value(message).unwrap(),
+                serde_json::to_value(&parsed).unwrap(),
+                "{:?} roundtrip mismatch",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_byte_roundtrip() {
+        for format in [Format::Json, Format::Cbor, Format::MessagePack] {
+            assert_eq!(Format::from_byte(format.as_byte()).unwrap(), format);
+        }
+        assert!(matches!(Format::from_byte(9), Err(ProtocolError::UnsupportedFormat(_))));
+    }
+
+    #[test]
+    fn test_every_op_roundtrips() {
+        for format in available_formats() {
+            assert_roundtrip(&all_ops(), format);
+        }
+    }
+
+    #[test]
+    fn test_every_event_roundtrips() {
+        for format in available_formats() {
+            assert_roundtrip(&all_events(), format);
+        }
+    }
+
+    #[test]
+    fn test_frame_header_layout() {
+        let frame = encode_frame(&Op::interrupt(), Format::Json).unwrap();
+        let len = u32::from_be_bytes([frame[0], frame[1], frame[2], frame[3]]) as usize;
+        assert_eq!(len, frame.len() - HEADER_LEN);
+        assert_eq!(frame[4], Format::Json.as_byte());
+        assert_eq!(frame[5], b'{');
+    }
+
+    #[test]
+    fn test_stream_mixed_formats() {
+        let mut bytes = Vec::new();
+        for format in available_formats() {
+            let mut writer = FrameWriter::new(&mut bytes, format);
+            for op in all_ops() {
+                writer.write(&op).unwrap();
+            }
+        }
+
+        let parsed: Vec<Op> = FrameReader::new(bytes.as_slice())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(parsed.len(), all_ops().len() * available_formats().len());
+    }
+
+    #[test]
+    fn test_reader_skips_bad_frames() {
+        let mut bytes = Vec::new();
+        bytes.extend(encode_frame(&Op::user_input("x".repeat(500)), Format::Json).unwrap());
+        bytes.extend_from_slice(&[0, 0, 0, 3, 0]);
+        bytes.extend_from_slice(b"{{{");
+        bytes.extend(encode_frame(&Op::interrupt(), Format::Json).unwrap());
+
+        let mut reader = FrameReader::<_, Op>::new(bytes.as_slice()).with_max_frame_size(200);
+        assert!(matches!(reader.read(), Some(Err(ProtocolError::FrameTooLarge { .. }))));
+        assert!(matches!(reader.read(), Some(Err(ProtocolError::DeserializationError { .. }))));
+        assert!(matches!(reader.read(), Some(Ok(Op::Interrupt { .. }))));
+        assert!(reader.read().is_none());
+    }
+
+    #[test]
+    fn test_reader_truncated_frame() {
+        let frame = encode_frame(&Op::interrupt(), Format::Json).unwrap();
+        let mut reader = FrameReader::<_, Op>::new(&frame[..frame.len() - 2]);
+        assert!(matches!(reader.read(), Some(Err(ProtocolError::TransportError(_)))));
+        assert!(reader.read().is_none());
+
+        let mut reader = FrameReader::<_, Op>::new(&frame[..2]);
+        assert!(matches!(reader.read(), Some(Err(ProtocolError::TransportError(_)))));
+        assert!(reader.read().is_none());
+    }
+
+    #[test]
+    fn test_reader_stops_after_io_error() {
+        struct BrokenPipe;
+
+        impl Read for BrokenPipe {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+        }
+
+        assert_eq!(FrameReader::<_, Op>::new(BrokenPipe).take(3).count(), 1);
+    }
+
+    #[test]
+    fn test_writer_rejects_oversized_frame() {
+        let mut writer = FrameWriter::new(Vec::new(), Format::Json).with_max_frame_size(8);
+        let result = writer.write(&Event::TaskInterrupted {
+            sub_id: crate::SubmissionId::new(),
+            task_id: crate::TaskId::new(),
+        });
+        assert!(matches!(result, Err(ProtocolError::FrameTooLarge { .. })));
+    }
+
+    #[cfg(not(feature = "cbor"))]
+    #[test]
+    fn test_unavailable_format() {
+        let result = encode_frame(&Op::interrupt(), Format::Cbor);
+        assert!(matches!(result, Err(ProtocolError::UnsupportedFormat(_))));
+    }
+}
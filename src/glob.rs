@@ -0,0 +1,109 @@
+//! Minimal glob matching for policy rules
+//!
+//! [`glob_match`] is for paths: `*` matches any run of characters except
+//! `/`, `**` matches across `/` (and `**/` also matches no directories at
+//! all), `?` matches one character other than `/`.
+//!
+//! [`wildcard_match`] is for names and commands, where `/` is nothing
+//! special: `*` matches any run of characters and `?` any one character.
+//!
+//! Everything else matches literally in both.
+
+/// Check whether `text` matches the glob `pattern`
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    match_from(&pattern, &text)
+}
+
+/// Check whether `text` matches `pattern`, with `*` and `?` crossing `/`
+pub(crate) fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*`, and the text position it is tried at
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, t));
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => {
+            if let Some(rest) = rest.strip_prefix(&['*']) {
+                if let Some(after) = rest.strip_prefix(&['/']) {
+                    if match_from(after, text) {
+                        return true;
+                    }
+                }
+                (0..=text.len()).any(|i| match_from(rest, &text[i..]))
+            } else {
+                (0..=text.len())
+                    .take_while(|&i| i == 0 || text[i - 1] != '/')
+                    .any(|i| match_from(rest, &text[i..]))
+            }
+        }
+        Some(('?', rest)) => {
+            text.first().is_some_and(|c| *c != '/') && match_from(rest, &text[1..])
+        }
+        Some((c, rest)) => text.first() == Some(c) && match_from(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // === Glob Tests ===
+
+    #[test]
+    fn test_literal_and_single_star() {
+        assert!(glob_match("shell", "shell"));
+        assert!(!glob_match("shell", "shell2"));
+        assert!(glob_match("mcp__github__*", "mcp__github__create_issue"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("/home/*", "/home/me/project"));
+        assert!(glob_match("file_?", "file_a"));
+        assert!(!glob_match("file_?", "file_"));
+    }
+
+    #[test]
+    fn test_wildcard_crosses_slash() {
+        assert!(wildcard_match("git push --force*", "git push --force origin feature/x"));
+        assert!(wildcard_match("rm -rf *", "rm -rf /"));
+        assert!(wildcard_match("*/bin/*", "/usr/bin/env"));
+        assert!(wildcard_match("file_?", "file_/"));
+        assert!(wildcard_match("*", ""));
+        assert!(!wildcard_match("rm -rf *", "rm -r /"));
+        assert!(!wildcard_match("a*b*c", "a/b/d"));
+    }
+
+    #[test]
+    fn test_double_star() {
+        assert!(glob_match("/home/**", "/home/me/project"));
+        assert!(glob_match("**/.env", ".env"));
+        assert!(glob_match("**/.env", "/srv/app/.env"));
+        assert!(glob_match("/srv/**/secrets/*", "/srv/secrets/key"));
+        assert!(!glob_match("**/.env", "/srv/app/.env.example"));
+    }
+}
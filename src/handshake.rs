@@ -0,0 +1,183 @@
+//! Connection handshake
+//!
+//! The first exchange on every connection is `Op::Hello` from the UI followed
+//! by `Event::Welcome` from the orchestrator. Both sides run [`negotiate`] on
+//! the advertised versions and capabilities, so an older UI can keep working
+//! against a newer orchestrator with the features it does not know disabled.
+
+use crate::envelope::ProtocolVersion;
+use crate::error::ProtocolError;
+use crate::events::Event;
+use crate::ids::*;
+use crate::models::Capability;
+use crate::ops::Op;
+
+/// Outcome of a successful handshake
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Negotiated {
+    /// Protocol version both sides will speak
+    pub version: ProtocolVersion,
+    /// Capabilities enabled for this connection
+    pub capabilities: Vec<Capability>,
+}
+
+impl Negotiated {
+    /// Check whether a capability was agreed on
+    pub fn supports(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability)
+    }
+}
+
+/// Negotiate a protocol version and capability set
+///
+/// Each side is assumed to speak every minor version up to the one it
+/// advertises, so the highest mutually supported version is the lower of the
+/// two. Capabilities are the intersection of both lists, in the order the
+/// client listed them; unknown capabilities are dropped.
+///
+/// Returns [`ProtocolError::VersionMismatch`] if the major versions differ.
+pub fn negotiate(
+    client_version: &ProtocolVersion,
+    client_capabilities: &[Capability],
+    server_version: &ProtocolVersion,
+    server_capabilities: &[Capability],
+) -> Result<Negotiated, ProtocolError> {
+    server_version.check_compatible(client_version)?;
+
+    let version = (*client_version).min(*server_version);
+
+    let mut capabilities = Vec::new();
+    for capability in client_capabilities {
+        if *capability != Capability::Unknown
+            && server_capabilities.contains(capability)
+            && !capabilities.contains(capability)
+        {
+            capabilities.push(*capability);
+        }
+    }
+
+    Ok(Negotiated { version, capabilities })
+}
+
+/// Answer an `Op::Hello` with the matching `Event::Welcome`
+///
+/// Returns [`ProtocolError::UnknownOperation`] if `op` is not a `Hello`.
+pub fn welcome(
+    op: &Op,
+    session_id: SessionId,
+    server_capabilities: &[Capability],
+) -> Result<(Negotiated, Event), ProtocolError> {
+    let Op::Hello { sub_id, protocol_version, capabilities, .. } = op else {
+        return Err(ProtocolError::UnknownOperation(
+            "expected hello as the first operation".into(),
+        ));
+    };
+
+    let server_version = ProtocolVersion::current();
+    let negotiated = negotiate(
+        protocol_version,
+        capabilities,
+        &server_version,
+        server_capabilities,
+    )?;
+
+    let event = Event::Welcome {
+        sub_id: sub_id.clone(),
+        session_id,
+        server_version,
+        accepted_capabilities: negotiated.capabilities.clone(),
+    };
+    Ok((negotiated, event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_picks_lower_minor() {
+        let negotiated = negotiate(
+            &ProtocolVersion::new(1, 2, 0),
+            &[],
+            &ProtocolVersion::new(1, 5, 3),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(negotiated.version, ProtocolVersion::new(1, 2, 0));
+
+        let negotiated = negotiate(
+            &ProtocolVersion::new(1, 7, 1),
+            &[],
+            &ProtocolVersion::new(1, 5, 3),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(negotiated.version, ProtocolVersion::new(1, 5, 3));
+    }
+
+    #[test]
+    fn test_negotiate_major_mismatch() {
+        let result = negotiate(
+            &ProtocolVersion::new(2, 0, 0),
+            &[],
+            &ProtocolVersion::new(1, 0, 0),
+            &[],
+        );
+        match result {
+            Err(ProtocolError::VersionMismatch { expected, actual }) => {
+                assert_eq!(expected, "1.0.0");
+                assert_eq!(actual, "2.0.0");
+            }
+            other => panic!("Expected VersionMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_negotiate_capability_intersection() {
+        let negotiated = negotiate(
+            &ProtocolVersion::current(),
+            &[
+                Capability::BlobAttachments,
+                Capability::Unknown,
+                Capability::StreamingDeltas,
+                Capability::BlobAttachments,
+            ],
+            &ProtocolVersion::current(),
+            &[Capability::StreamingDeltas, Capability::BlobAttachments, Capability::Unknown],
+        )
+        .unwrap();
+
+        assert_eq!(
+            negotiated.capabilities,
+            vec![Capability::BlobAttachments, Capability::StreamingDeltas]
+        );
+        assert!(negotiated.supports(Capability::StreamingDeltas));
+        assert!(!negotiated.supports(Capability::HierarchyPatches));
+    }
+
+    #[test]
+    fn test_welcome_replies_to_hello() {
+        let offered = vec![Capability::HierarchyPatches, Capability::StreamingDeltas];
+        let hello = Op::hello("lair", offered);
+        let session_id = SessionId::new();
+
+        let (negotiated, event) =
+            welcome(&hello, session_id, &[Capability::HierarchyPatches]).unwrap();
+        assert_eq!(negotiated.capabilities, vec![Capability::HierarchyPatches]);
+
+        match event {
+            Event::Welcome { sub_id, session_id: sid, accepted_capabilities, .. } => {
+                assert_eq!(&sub_id, hello.sub_id());
+                assert_eq!(sid, session_id);
+                assert_eq!(accepted_capabilities, vec![Capability::HierarchyPatches]);
+            }
+            _ => panic!("Wrong variant"),
+        }
+    }
+
+    #[test]
+    fn test_welcome_rejects_other_ops() {
+        let result = welcome(&Op::interrupt(), SessionId::new(), Capability::ALL);
+        assert!(matches!(result, Err(ProtocolError::UnknownOperation(_))));
+    }
+}
@@ -0,0 +1,654 @@
+//! Agent hierarchy operations
+//!
+//! [`AgentTree`] can be walked depth- or breadth-first, searched by
+//! `AgentId`, summarised by status and role, and checked against each
+//! agent's `AgentConfig::max_children`.
+//!
+//! With 50+ agents, resending the whole [`AgentTree`] on every status change
+//! is wasteful. [`AgentTree::diff`] computes the [`HierarchyOp`]s that turn
+//! one snapshot into another, and [`AgentTree::apply`] replays them, so a
+//! peer that negotiated `Capability::HierarchyPatches` can keep its own copy
+//! of the tree in sync from `Event::HierarchyPatch` alone.
+//!
+//! ```
+//! use warhorn::{AgentId, AgentRole, AgentStatus, AgentTree};
+//!
+//! let old = AgentTree {
+//!     agent_id: AgentId::new(),
+//!     role: AgentRole::Orchestrator,
+//!     status: AgentStatus::Running,
+//!     task_summary: None,
+//!     children: vec![],
+//! };
+//! let mut new = old.clone();
+//! new.status = AgentStatus::Completed;
+//!
+//! let patch = AgentTree::diff(&old, &new);
+//! let mut copy = old.clone();
+//! copy.apply(&patch).unwrap();
+//! assert_eq!(copy, new);
+//! ```
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::error::ProtocolError;
+use crate::ids::AgentId;
+use crate::models::{AgentConfig, AgentRole, AgentTree, HierarchyOp};
+
+/// Depth-first (pre-order) iterator over an [`AgentTree`]
+#[derive(Debug, Clone)]
+pub struct DepthFirst<'a> {
+    stack: Vec<&'a AgentTree>,
+}
+
+impl<'a> Iterator for DepthFirst<'a> {
+    type Item = &'a AgentTree;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.iter().rev());
+        Some(node)
+    }
+}
+
+/// Breadth-first (level-order) iterator over an [`AgentTree`]
+#[derive(Debug, Clone)]
+pub struct BreadthFirst<'a> {
+    queue: VecDeque<&'a AgentTree>,
+}
+
+impl<'a> Iterator for BreadthFirst<'a> {
+    type Item = &'a AgentTree;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.children.iter());
+        Some(node)
+    }
+}
+
+/// An agent with more children than its `max_children` allows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChildLimitViolation {
+    pub agent_id: AgentId,
+    pub children: usize,
+    pub max_children: usize,
+}
+
+impl AgentTree {
+    /// Iterate over every node, parents before children
+    pub fn iter_depth_first(&self) -> DepthFirst<'_> {
+        DepthFirst { stack: vec![self] }
+    }
+
+    /// Iterate over every node, level by level
+    pub fn iter_breadth_first(&self) -> BreadthFirst<'_> {
+        BreadthFirst { queue: VecDeque::from([self]) }
+    }
+
+    /// Number of agents in the tree, including the root
+    pub fn agent_count(&self) -> usize {
+        self.iter_depth_first().count()
+    }
+
+    /// Find the node for an agent
+    pub fn find(&self, agent_id: &AgentId) -> Option<&AgentTree> {
+        self.iter_depth_first().find(|node| node.agent_id == *agent_id)
+    }
+
+    /// Find the node for an agent, mutably
+    pub fn find_mut(&mut self, agent_id: &AgentId) -> Option<&mut AgentTree> {
+        if self.agent_id == *agent_id {
+            return Some(self);
+        }
+        self.children.iter_mut().find_map(|child| child.find_mut(agent_id))
+    }
+
+    /// Nodes from the root down to `agent_id`, both included
+    pub fn path_to(&self, agent_id: &AgentId) -> Option<Vec<&AgentTree>> {
+        if self.agent_id == *agent_id {
+            return Some(vec![self]);
+        }
+        self.children.iter().find_map(|child| {
+            let mut path = child.path_to(agent_id)?;
+            path.insert(0, self);
+            Some(path)
+        })
+    }
+
+    /// Ancestors of `agent_id`, nearest first
+    ///
+    /// Empty for the root and for agents not in the tree.
+    pub fn ancestors(&self, agent_id: &AgentId) -> Vec<&AgentTree> {
+        let mut path = self.path_to(agent_id).unwrap_or_default();
+        path.pop();
+        path.reverse();
+        path
+    }
+
+    /// Descendants of `agent_id` in depth-first order, excluding the agent
+    ///
+    /// Empty for leaves and for agents not in the tree.
+    pub fn descendants(&self, agent_id: &AgentId) -> Vec<&AgentTree> {
+        self.find(agent_id)
+            .map(|node| node.iter_depth_first().skip(1).collect())
+            .unwrap_or_default()
+    }
+
+    /// Distance from the root to `agent_id` (the root is at depth 0)
+    pub fn depth(&self, agent_id: &AgentId) -> Option<usize> {
+        self.path_to(agent_id).map(|path| path.len() - 1)
+    }
+
+    /// Number of agents in each status, keyed by [`AgentStatus::name`]
+    ///
+    /// [`AgentStatus::name`]: crate::models::AgentStatus::name
+    pub fn count_by_status(&self) -> BTreeMap<&'static str, usize> {
+        let mut counts = BTreeMap::new();
+        for node in self.iter_depth_first() {
+            *counts.entry(node.status.name()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Number of agents in each role
+    pub fn count_by_role(&self) -> HashMap<AgentRole, usize> {
+        let mut counts = HashMap::new();
+        for node in self.iter_depth_first() {
+            *counts.entry(node.role.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Check whether `parent_id` may take one more child under `config`
+    ///
+    /// False if the agent is not in the tree, cannot spawn, or is already at
+    /// its `max_children`.
+    pub fn has_child_capacity(&self, parent_id: &AgentId, config: &AgentConfig) -> bool {
+        let Some(parent) = self.find(parent_id) else {
+            return false;
+        };
+        config.can_spawn
+            && config.max_children.is_none_or(|max| parent.children.len() < max)
+    }
+
+    /// Agents with more children than their `max_children`
+    ///
+    /// `config` looks up each agent's configuration; agents without one are
+    /// not checked.
+    pub fn child_limit_violations<'a>(
+        &self,
+        config: impl Fn(&AgentId) -> Option<&'a AgentConfig>,
+    ) -> Vec<ChildLimitViolation> {
+        self.iter_depth_first()
+            .filter_map(|node| {
+                let max_children = config(&node.agent_id)?.max_children?;
+                (node.children.len() > max_children).then_some(ChildLimitViolation {
+                    agent_id: node.agent_id,
+                    children: node.children.len(),
+                    max_children,
+                })
+            })
+            .collect()
+    }
+
+    /// Compute the ops that turn `old` into `new`
+    ///
+    /// Agents are matched by `agent_id`. An agent whose parent or role
+    /// changed, or whose position among its siblings moved, is removed and
+    /// re-inserted. If the roots differ the patch is a single
+    /// [`HierarchyOp::ReplaceRoot`].
+    pub fn diff(old: &AgentTree, new: &AgentTree) -> Vec<HierarchyOp> {
+        if old.agent_id != new.agent_id || old.role != new.role {
+            return vec![HierarchyOp::ReplaceRoot { root: new.clone() }];
+        }
+
+        let mut placement = HashMap::new();
+        index_placement(new, None, &mut placement);
+
+        let mut ops = Vec::new();
+        let mut working = old.clone();
+        remove_misplaced(&mut working, &placement, &mut ops);
+        diff_node(&working, new, &mut ops);
+        ops
+    }
+
+    /// Apply a patch in order
+    ///
+    /// Returns [`ProtocolError::InvalidPatch`] if an op names an agent that
+    /// is not in the tree, inserts an agent that already is, removes the
+    /// root, or inserts past the end of a child list. Ops before the failing
+    /// one stay applied.
+    pub fn apply(&mut self, patch: &[HierarchyOp]) -> Result<(), ProtocolError> {
+        for op in patch {
+            self.apply_op(op)?;
+        }
+        Ok(())
+    }
+
+    fn apply_op(&mut self, op: &HierarchyOp) -> Result<(), ProtocolError> {
+        match op {
+            HierarchyOp::ReplaceRoot { root } => {
+                *self = root.clone();
+            }
+            HierarchyOp::InsertChild { parent_id, index, node } => {
+                if self.find(&node.agent_id).is_some() {
+                    return Err(ProtocolError::InvalidPatch(format!(
+                        "{} is already in the tree",
+                        node.agent_id
+                    )));
+                }
+                let parent = self.node_mut(parent_id)?;
+                if *index > parent.children.len() {
+                    return Err(ProtocolError::InvalidPatch(format!(
+                        "index {index} is past the {} children of {parent_id}",
+                        parent.children.len()
+                    )));
+                }
+                parent.children.insert(*index, node.clone());
+            }
+            HierarchyOp::RemoveSubtree { agent_id } => {
+                if *agent_id == self.agent_id {
+                    return Err(ProtocolError::InvalidPatch("cannot remove the root".into()));
+                }
+                if !self.remove(agent_id) {
+                    return Err(unknown_agent(agent_id));
+                }
+            }
+            HierarchyOp::UpdateStatus { agent_id, status } => {
+                self.node_mut(agent_id)?.status = status.clone();
+            }
+            HierarchyOp::UpdateTaskSummary { agent_id, task_summary } => {
+                self.node_mut(agent_id)?.task_summary = task_summary.clone();
+            }
+        }
+        Ok(())
+    }
+
+    fn node_mut(&mut self, agent_id: &AgentId) -> Result<&mut AgentTree, ProtocolError> {
+        self.find_mut(agent_id).ok_or_else(|| unknown_agent(agent_id))
+    }
+
+    fn remove(&mut self, agent_id: &AgentId) -> bool {
+        if let Some(position) = self.children.iter().position(|child| child.agent_id == *agent_id) {
+            self.children.remove(position);
+            return true;
+        }
+        self.children.iter_mut().any(|child| child.remove(agent_id))
+    }
+}
+
+fn unknown_agent(agent_id: &AgentId) -> ProtocolError {
+    ProtocolError::InvalidPatch(format!("{agent_id} is not in the tree"))
+}
+
+/// Parent and role of every agent in the target tree
+fn index_placement<'a>(
+    node: &'a AgentTree,
+    parent_id: Option<AgentId>,
+    placement: &mut HashMap<AgentId, (Option<AgentId>, &'a AgentRole)>,
+) {
+    placement.insert(node.agent_id, (parent_id, &node.role));
+    for child in &node.children {
+        index_placement(child, Some(node.agent_id), placement);
+    }
+}
+
+/// Remove every subtree whose root is gone or has a new parent or role
+fn remove_misplaced(
+    node: &mut AgentTree,
+    placement: &HashMap<AgentId, (Option<AgentId>, &AgentRole)>,
+    ops: &mut Vec<HierarchyOp>,
+) {
+    let parent_id = node.agent_id;
+    node.children.retain(|child| {
+        let keep = placement.get(&child.agent_id).is_some_and(|(parent, role)| {
+            *parent == Some(parent_id) && **role == child.role
+        });
+        if !keep {
+            ops.push(HierarchyOp::RemoveSubtree { agent_id: child.agent_id });
+        }
+        keep
+    });
+    for child in &mut node.children {
+        remove_misplaced(child, placement, ops);
+    }
+}
+
+/// Diff two nodes with the same id and role whose children share a parent
+fn diff_node(old: &AgentTree, new: &AgentTree, ops: &mut Vec<HierarchyOp>) {
+    if old.status != new.status {
+        ops.push(HierarchyOp::UpdateStatus {
+            agent_id: new.agent_id,
+            status: new.status.clone(),
+        });
+    }
+    if old.task_summary != new.task_summary {
+        ops.push(HierarchyOp::UpdateTaskSummary {
+            agent_id: new.agent_id,
+            task_summary: new.task_summary.clone(),
+        });
+    }
+
+    // Children as the peer will see them while the patch is applied
+    let mut current: Vec<&AgentTree> = old.children.iter().collect();
+    for (index, child) in new.children.iter().enumerate() {
+        if current.get(index).is_some_and(|existing| existing.agent_id == child.agent_id) {
+            diff_node(current[index], child, ops);
+            continue;
+        }
+        let moved = current.iter().position(|existing| existing.agent_id == child.agent_id);
+        if let Some(position) = moved {
+            current.remove(position);
+            ops.push(HierarchyOp::RemoveSubtree { agent_id: child.agent_id });
+        }
+        ops.push(HierarchyOp::InsertChild {
+            parent_id: new.agent_id,
+            index,
+            node: child.clone(),
+        });
+        current.insert(index, child);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::sample_tree;
+    use crate::models::AgentStatus;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+
+    fn leaf(role: AgentRole) -> AgentTree {
+        AgentTree {
+            agent_id: AgentId::new(),
+            role,
+            status: AgentStatus::Running,
+            task_summary: None,
+            children: vec![],
+        }
+    }
+
+    fn patched(old: &AgentTree, new: &AgentTree) -> AgentTree {
+        let patch = AgentTree::diff(old, new);
+        let mut tree = old.clone();
+        tree.apply(&patch).unwrap();
+        tree
+    }
+
+    /// Root with a lead (with two workers) and a scout
+    fn deep_tree() -> AgentTree {
+        let mut lead = leaf(AgentRole::DomainLead { domain: "backend".into() });
+        lead.children = vec![leaf(AgentRole::Worker), leaf(AgentRole::Worker)];
+        lead.children[1].status = AgentStatus::Waiting { reason: "approval".into() };
+        let mut root = leaf(AgentRole::Orchestrator);
+        root.children = vec![lead, leaf(AgentRole::Scout)];
+        root
+    }
+
+    fn ids<'a>(nodes: impl IntoIterator<Item = &'a AgentTree>) -> Vec<AgentId> {
+        nodes.into_iter().map(|node| node.agent_id).collect()
+    }
+
+    // === Traversal Tests ===
+
+    #[test]
+    fn test_depth_and_breadth_first_order() {
+        let tree = deep_tree();
+        let lead = &tree.children[0];
+        let scout = &tree.children[1];
+        let (first, second) = (lead.children[0].agent_id, lead.children[1].agent_id);
+
+        assert_eq!(
+            ids(tree.iter_depth_first()),
+            [tree.agent_id, lead.agent_id, first, second, scout.agent_id]
+        );
+        assert_eq!(
+            ids(tree.iter_breadth_first()),
+            [tree.agent_id, lead.agent_id, scout.agent_id, first, second]
+        );
+        assert_eq!(tree.agent_count(), 5);
+    }
+
+    #[test]
+    fn test_find_and_paths() {
+        let tree = deep_tree();
+        let lead = &tree.children[0];
+        let worker = &lead.children[1];
+
+        assert_eq!(tree.find(&worker.agent_id), Some(worker));
+        assert!(tree.find(&AgentId::new()).is_none());
+
+        assert_eq!(
+            ids(tree.path_to(&worker.agent_id).unwrap()),
+            [tree.agent_id, lead.agent_id, worker.agent_id]
+        );
+        assert_eq!(ids(tree.ancestors(&worker.agent_id)), [lead.agent_id, tree.agent_id]);
+        assert!(tree.ancestors(&tree.agent_id).is_empty());
+        assert_eq!(ids(tree.descendants(&lead.agent_id)), ids(&lead.children));
+        assert!(tree.descendants(&AgentId::new()).is_empty());
+
+        assert_eq!(tree.depth(&tree.agent_id), Some(0));
+        assert_eq!(tree.depth(&worker.agent_id), Some(2));
+        assert_eq!(tree.depth(&AgentId::new()), None);
+    }
+
+    #[test]
+    fn test_counts() {
+        let tree = deep_tree();
+        let by_status = tree.count_by_status();
+        assert_eq!(by_status["running"], 4);
+        assert_eq!(by_status["waiting"], 1);
+
+        let by_role = tree.count_by_role();
+        assert_eq!(by_role[&AgentRole::Worker], 2);
+        assert_eq!(by_role[&AgentRole::DomainLead { domain: "backend".into() }], 1);
+    }
+
+    // === Child Limit Tests ===
+
+    #[test]
+    fn test_child_capacity() {
+        let tree = deep_tree();
+        let lead = tree.children[0].agent_id;
+        let mut config =
+            AgentConfig { can_spawn: true, max_children: Some(2), ..Default::default() };
+        assert!(!tree.has_child_capacity(&lead, &config));
+
+        config.max_children = Some(3);
+        assert!(tree.has_child_capacity(&lead, &config));
+
+        config.max_children = None;
+        assert!(tree.has_child_capacity(&lead, &config));
+
+        config.can_spawn = false;
+        assert!(!tree.has_child_capacity(&lead, &config));
+        let spawner = AgentConfig { can_spawn: true, ..Default::default() };
+        assert!(!tree.has_child_capacity(&AgentId::new(), &spawner));
+    }
+
+    #[test]
+    fn test_child_limit_violations() {
+        let tree = deep_tree();
+        let lead = tree.children[0].agent_id;
+        let configs: HashMap<AgentId, AgentConfig> = [
+            (tree.agent_id, AgentConfig { max_children: Some(2), ..Default::default() }),
+            (lead, AgentConfig { max_children: Some(1), ..Default::default() }),
+        ]
+        .into();
+
+        let violations = tree.child_limit_violations(|id| configs.get(id));
+        assert_eq!(
+            violations,
+            [ChildLimitViolation { agent_id: lead, children: 2, max_children: 1 }]
+        );
+    }
+
+    // === Diff Tests ===
+
+    #[test]
+    fn test_identical_trees_have_empty_diff() {
+        let tree = sample_tree();
+        assert!(AgentTree::diff(&tree, &tree).is_empty());
+    }
+
+    #[test]
+    fn test_status_and_summary_updates() {
+        let old = sample_tree();
+        let mut new = old.clone();
+        new.children[0].status = AgentStatus::Running;
+        new.children[1].task_summary = None;
+
+        let patch = AgentTree::diff(&old, &new);
+        assert_eq!(patch.len(), 2);
+        assert!(matches!(patch[0], HierarchyOp::UpdateStatus { .. }));
+        assert!(matches!(patch[1], HierarchyOp::UpdateTaskSummary { .. }));
+        assert_eq!(patched(&old, &new), new);
+    }
+
+    #[test]
+    fn test_insert_and_remove() {
+        let old = sample_tree();
+        let mut new = old.clone();
+        new.children.remove(0);
+        new.children[0].children.push(leaf(AgentRole::Scout));
+
+        let patch = AgentTree::diff(&old, &new);
+        let removed = old.children[0].agent_id;
+        assert!(matches!(patch[0], HierarchyOp::RemoveSubtree { agent_id } if agent_id == removed));
+        assert!(matches!(patch[1], HierarchyOp::InsertChild { index: 0, .. }));
+        assert_eq!(patched(&old, &new), new);
+    }
+
+    #[test]
+    fn test_moved_subtree() {
+        let old = sample_tree();
+        let mut new = old.clone();
+        let moved = new.children.remove(1);
+        new.children[0].children.push(moved);
+        assert_eq!(patched(&old, &new), new);
+    }
+
+    #[test]
+    fn test_reordered_children() {
+        let old = sample_tree();
+        let mut new = old.clone();
+        new.children.reverse();
+        assert_eq!(patched(&old, &new), new);
+    }
+
+    #[test]
+    fn test_new_root() {
+        let old = sample_tree();
+        let new = leaf(AgentRole::Orchestrator);
+        let replace = HierarchyOp::ReplaceRoot { root: new.clone() };
+        assert_eq!(AgentTree::diff(&old, &new), vec![replace]);
+        assert_eq!(patched(&old, &new), new);
+    }
+
+    // === Apply Tests ===
+
+    #[test]
+    fn test_apply_rejects_unknown_agent() {
+        let mut tree = sample_tree();
+        let result = tree.apply(&[HierarchyOp::UpdateStatus {
+            agent_id: AgentId::new(),
+            status: AgentStatus::Failed,
+        }]);
+        assert!(matches!(result, Err(ProtocolError::InvalidPatch(_))));
+    }
+
+    #[test]
+    fn test_apply_rejects_duplicate_and_bad_index() {
+        let mut tree = sample_tree();
+        let duplicate = HierarchyOp::InsertChild {
+            parent_id: tree.agent_id,
+            index: 0,
+            node: tree.children[0].clone(),
+        };
+        assert!(tree.apply(&[duplicate]).is_err());
+
+        let past_end = HierarchyOp::InsertChild {
+            parent_id: tree.agent_id,
+            index: 3,
+            node: leaf(AgentRole::Worker),
+        };
+        assert!(tree.apply(&[past_end]).is_err());
+
+        let root = HierarchyOp::RemoveSubtree { agent_id: tree.agent_id };
+        assert!(tree.apply(&[root]).is_err());
+    }
+
+    #[test]
+    fn test_patch_roundtrip() {
+        let patch = vec![
+            HierarchyOp::RemoveSubtree { agent_id: AgentId::new() },
+            HierarchyOp::UpdateTaskSummary { agent_id: AgentId::new(), task_summary: None },
+        ];
+        let json = serde_json::to_string(&patch).unwrap();
+        assert!(json.contains("\"type\":\"remove_subtree\""));
+        let parsed: Vec<HierarchyOp> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, patch);
+    }
+
+    // === Property Tests ===
+
+    /// A tree under `root` with child ids drawn from `pool`, so that two
+    /// generated trees share agents
+    fn arb_tree(root: AgentId, pool: Vec<AgentId>) -> impl Strategy<Value = AgentTree> {
+        let roles = prop_oneof![
+            Just(AgentRole::Worker),
+            Just(AgentRole::Scout),
+            Just(AgentRole::DomainLead { domain: "backend".into() }),
+        ];
+        let statuses = prop_oneof![
+            Just(AgentStatus::Running),
+            Just(AgentStatus::Completed),
+            Just(AgentStatus::Waiting { reason: "approval".into() }),
+        ];
+        let summaries = prop::option::of(prop_oneof![Just("a".to_string()), Just("b".to_string())]);
+        let node = (prop::sample::select(pool), roles, statuses, summaries).prop_map(
+            |(agent_id, role, status, task_summary)| AgentTree {
+                agent_id,
+                role,
+                status,
+                task_summary,
+                children: vec![],
+            },
+        );
+        node.prop_recursive(4, 24, 4, |inner| {
+            (inner.clone(), prop::collection::vec(inner, 0..4)).prop_map(|(mut node, children)| {
+                node.children = children;
+                node
+            })
+        })
+        .prop_map(move |mut tree| {
+            tree.agent_id = root;
+            tree.role = AgentRole::Orchestrator;
+            let mut seen = HashSet::from([root]);
+            dedup(&mut tree, &mut seen);
+            tree
+        })
+    }
+
+    /// Drop every subtree whose root id already appeared
+    fn dedup(node: &mut AgentTree, seen: &mut HashSet<AgentId>) {
+        node.children.retain(|child| seen.insert(child.agent_id));
+        for child in &mut node.children {
+            dedup(child, seen);
+        }
+    }
+
+    fn arb_pair() -> impl Strategy<Value = (AgentTree, AgentTree)> {
+        let root = AgentId::new();
+        let pool: Vec<AgentId> = (0..10).map(|_| AgentId::new()).collect();
+        (arb_tree(root, pool.clone()), arb_tree(root, pool))
+    }
+
+    proptest! {
+        #[test]
+        fn prop_apply_diff_reproduces_target((old, new) in arb_pair()) {
+            prop_assert_eq!(patched(&old, &new), new);
+        }
+    }
+}
@@ -6,6 +6,7 @@ use uuid::Uuid;
 
 /// Unique identifier for an agent in the hierarchy
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AgentId(Uuid);
 
 impl AgentId {
@@ -36,6 +37,7 @@ impl fmt::Display for AgentId {
 
 /// Unique identifier for a task
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TaskId(Uuid);
 
 impl TaskId {
@@ -62,6 +64,7 @@ impl fmt::Display for TaskId {
 
 /// Unique identifier for a tool call
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CallId(Uuid);
 
 impl CallId {
@@ -88,6 +91,7 @@ impl fmt::Display for CallId {
 
 /// Unique identifier for a session
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SessionId(Uuid);
 
 impl SessionId {
@@ -114,6 +118,7 @@ impl fmt::Display for SessionId {
 
 /// Unique identifier for a checkpoint
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CheckpointId(Uuid);
 
 impl CheckpointId {
@@ -140,6 +145,7 @@ impl fmt::Display for CheckpointId {
 
 /// Submission ID for correlating operations with events
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SubmissionId(String);
 
 impl SubmissionId {
@@ -23,18 +23,69 @@
 //! - Unix domain sockets
 //! - stdio (for MCP compatibility)
 //! - WebSocket (for remote agents)
+//!
+//! Ready-made async transports live in [`transport`] behind the `transport`
+//! feature; [`codec`] and [`framing`] handle blocking I/O, and [`event_log`]
+//! records event streams to JSONL files for later replay.
+//!
+//! [`correlation`] matches incoming events to the ops that caused them and
+//! reports when each exchange is over; [`state`] folds the event stream into
+//! a queryable `SessionState`; [`lifecycle`] flags streams that break the
+//! agent status or task lifecycle; [`policy`] evaluates the approval rules
+//! behind `ApprovalMode::Custom`, and [`shell`] rates shell commands for
+//! `ApprovalRequired`. [`network`] gives `NetworkPolicy` allowlists their
+//! matching rules, and [`sandbox`] decides which paths may be read or written.
+//! [`pricing`] turns token usage into exact costs per agent, role, model and
+//! task; [`budget`] checks it against agent and session token budgets.
+//!
+//! [`hierarchy`] walks, queries and patches `AgentTree`s; [`render`] draws
+//! them, and `TaskPlan`s, as ASCII trees, Graphviz DOT or Mermaid; [`plan`]
+//! validates a plan's dependency graph and orders its steps into waves.
+//!
+//! With the `schema` feature, `schema` generates JSON Schema documents that
+//! non-Rust clients can validate payloads against; `typescript` renders them
+//! as `.d.ts` declarations.
 
 pub mod ids;
 pub mod ops;
 pub mod events;
 pub mod models;
 pub mod error;
+pub mod envelope;
+pub mod codec;
+pub mod framing;
+pub mod handshake;
+pub mod correlation;
+pub mod state;
+pub mod hierarchy;
+pub mod render;
+pub mod event_log;
+pub mod lifecycle;
+pub mod policy;
+pub mod shell;
+pub mod network;
+pub mod sandbox;
+pub mod pricing;
+pub mod budget;
+pub mod plan;
+mod glob;
+#[cfg(feature = "transport")]
+pub mod transport;
+#[cfg(feature = "schema")]
+pub mod schema;
+#[cfg(feature = "typescript")]
+pub mod typescript;
 
 pub use ids::*;
 pub use ops::Op;
 pub use events::Event;
 pub use models::*;
 pub use error::ProtocolError;
+pub use envelope::{Envelope, ProtocolVersion, Sequencer};
+pub use handshake::{negotiate, Negotiated};
+
+#[cfg(test)]
+mod fixtures;
 
 /// Protocol version for compatibility checking
 pub const PROTOCOL_VERSION: &str = "0.1.0";
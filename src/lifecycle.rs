@@ -0,0 +1,581 @@
+//! Lifecycle rules for agents and tasks
+//!
+//! An agent moves forward through its statuses and stops at a terminal one:
+//!
+//! ```text
+//! spawning ──▶ initializing ──▶ running ◀──▶ waiting
+//!     │             │              │            │
+//!     └─────────────┴──────────────┴────────────┴──▶ completed | failed | terminated
+//! ```
+//!
+//! Spawning may also go straight to running, initializing straight to
+//! waiting, and waiting may be re-entered with a new reason. Nothing leaves
+//! `completed`, `failed` or `terminated`.
+//!
+//! [`AgentStatus::transition`] enforces this for one agent;
+//! [`AgentLifecycleValidator`] checks a whole `Event` stream.
+//!
+//! Tasks follow a simpler rule: `TaskStarted`, then `TurnComplete`s with
+//! strictly increasing turn numbers, then exactly one of `TaskComplete`,
+//! `TaskFailed` or `TaskInterrupted`, after which nothing may mention the
+//! task again. Usage and plan step events that name a task count as
+//! mentions. [`TaskLifecycleValidator`] checks this.
+
+use std::collections::HashMap;
+use thiserror::Error;
+
+use crate::events::Event;
+use crate::ids::{AgentId, TaskId};
+use crate::models::AgentStatus;
+
+/// A status change the lifecycle does not allow
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Illegal agent status transition from {} to {}", from.name(), to.name())]
+pub struct IllegalTransition {
+    pub from: AgentStatus,
+    pub to: AgentStatus,
+}
+
+impl AgentStatus {
+    /// Check whether no further transitions are possible
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, AgentStatus::Completed | AgentStatus::Failed | AgentStatus::Terminated)
+    }
+
+    /// Check whether the lifecycle allows moving from this status to `next`
+    pub fn can_transition_to(&self, next: &AgentStatus) -> bool {
+        use AgentStatus::*;
+
+        if self.is_terminal() {
+            return false;
+        }
+        if next.is_terminal() {
+            return true;
+        }
+        matches!(
+            (self, next),
+            (Spawning, Initializing | Running)
+                | (Initializing, Running | Waiting { .. })
+                | (Running, Waiting { .. })
+                | (Waiting { .. }, Running | Waiting { .. })
+        )
+    }
+
+    /// Move to `next`, or leave the status unchanged if that is not allowed
+    pub fn transition(&mut self, next: AgentStatus) -> Result<(), IllegalTransition> {
+        if !self.can_transition_to(&next) {
+            return Err(IllegalTransition { from: self.clone(), to: next });
+        }
+        *self = next;
+        Ok(())
+    }
+}
+
+/// A lifecycle rule broken by an event stream
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LifecycleViolation {
+    /// Status changed, completed or terminated before `AgentSpawned`
+    #[error("{agent_id} reported {event} before it was spawned")]
+    NotSpawned { agent_id: AgentId, event: &'static str },
+
+    /// `AgentSpawned` for an agent that already exists
+    #[error("{agent_id} was spawned twice")]
+    DuplicateSpawn { agent_id: AgentId },
+
+    /// Status change the lifecycle does not allow
+    #[error("{agent_id}: {transition}")]
+    IllegalTransition { agent_id: AgentId, transition: IllegalTransition },
+}
+
+/// Checks `AgentStatusChanged` events against the agent lifecycle
+///
+/// `AgentSpawned` starts an agent in `spawning`; `AgentComplete` moves it to
+/// `completed` or `failed` depending on the result, and `AgentTerminated` to
+/// `terminated`. After an illegal move the validator adopts the reported
+/// status, so each bad edge is reported once.
+#[derive(Debug, Clone, Default)]
+pub struct AgentLifecycleValidator {
+    agents: HashMap<AgentId, AgentStatus>,
+}
+
+impl AgentLifecycleValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check one event; events that do not affect agent status always pass
+    pub fn check(&mut self, event: &Event) -> Result<(), LifecycleViolation> {
+        let (agent_id, next, kind) = match event {
+            Event::AgentSpawned { agent_id, .. } => {
+                if self.agents.contains_key(agent_id) {
+                    return Err(LifecycleViolation::DuplicateSpawn { agent_id: *agent_id });
+                }
+                self.agents.insert(*agent_id, AgentStatus::Spawning);
+                return Ok(());
+            }
+            Event::AgentStatusChanged { agent_id, status, .. } => {
+                (agent_id, status.clone(), "agent_status_changed")
+            }
+            Event::AgentComplete { agent_id, result, .. } => {
+                let status =
+                    if result.success { AgentStatus::Completed } else { AgentStatus::Failed };
+                (agent_id, status, "agent_complete")
+            }
+            Event::AgentTerminated { agent_id, .. } => {
+                (agent_id, AgentStatus::Terminated, "agent_terminated")
+            }
+            _ => return Ok(()),
+        };
+
+        let Some(current) = self.agents.get_mut(agent_id) else {
+            return Err(LifecycleViolation::NotSpawned { agent_id: *agent_id, event: kind });
+        };
+        if let Err(transition) = current.transition(next.clone()) {
+            *current = next;
+            return Err(LifecycleViolation::IllegalTransition { agent_id: *agent_id, transition });
+        }
+        Ok(())
+    }
+
+    /// Current status of an agent seen so far
+    pub fn status(&self, agent_id: &AgentId) -> Option<&AgentStatus> {
+        self.agents.get(agent_id)
+    }
+}
+
+/// Check a whole stream, returning every violation in order
+pub fn validate_agent_lifecycle<'a>(
+    events: impl IntoIterator<Item = &'a Event>,
+) -> Vec<LifecycleViolation> {
+    let mut validator = AgentLifecycleValidator::new();
+    events
+        .into_iter()
+        .filter_map(|event| validator.check(event).err())
+        .collect()
+}
+
+/// A task rule broken by an event stream
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TaskViolation {
+    /// Event for a task that never started
+    #[error("{task_id} reported {event} before it was started")]
+    NotStarted { task_id: TaskId, event: &'static str },
+
+    /// `TaskStarted` for a task that already exists
+    #[error("{task_id} was started twice")]
+    DuplicateStart { task_id: TaskId },
+
+    /// `TurnComplete` whose number is not above the previous one
+    #[error("{task_id} completed turn {turn} after turn {previous}")]
+    TurnOutOfOrder { task_id: TaskId, previous: u32, turn: u32 },
+
+    /// Event for a task that already completed, failed or was interrupted
+    #[error("{task_id} reported {event} after it finished")]
+    AfterTerminal { task_id: TaskId, event: &'static str },
+
+    /// `TaskComplete` whose `TaskResult` names a different task
+    #[error("{task_id} completed with the result of {result_task_id}")]
+    ResultMismatch { task_id: TaskId, result_task_id: TaskId },
+
+    /// Stream ended before the task reached a terminal event
+    #[error("{task_id} never finished")]
+    Unfinished { task_id: TaskId },
+}
+
+#[derive(Debug, Clone, Default)]
+struct TaskProgress {
+    last_turn: Option<u32>,
+    finished: bool,
+}
+
+/// Checks task events against the task lifecycle
+///
+/// Call [`check`](Self::check) for each event, then [`finish`](Self::finish)
+/// when the stream ends to catch tasks that never reached a terminal event.
+#[derive(Debug, Clone, Default)]
+pub struct TaskLifecycleValidator {
+    tasks: HashMap<TaskId, TaskProgress>,
+}
+
+impl TaskLifecycleValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check one event; events that do not mention a task always pass
+    pub fn check(&mut self, event: &Event) -> Result<(), TaskViolation> {
+        let (task_id, kind) = match event {
+            Event::TaskStarted { task_id, .. } => {
+                if self.tasks.contains_key(task_id) {
+                    return Err(TaskViolation::DuplicateStart { task_id: *task_id });
+                }
+                self.tasks.insert(*task_id, TaskProgress::default());
+                return Ok(());
+            }
+            Event::TurnComplete { task_id, .. } => (task_id, "turn_complete"),
+            Event::TaskComplete { task_id, .. } => (task_id, "task_complete"),
+            Event::TaskFailed { task_id, .. } => (task_id, "task_failed"),
+            Event::TaskInterrupted { task_id, .. } => (task_id, "task_interrupted"),
+            Event::UsageUpdate { task_id: Some(task_id), .. } => (task_id, "usage_update"),
+            Event::PlanStepStarted { task_id, .. } => (task_id, "plan_step_started"),
+            Event::PlanStepCompleted { task_id, .. } => (task_id, "plan_step_completed"),
+            Event::PlanStepFailed { task_id, .. } => (task_id, "plan_step_failed"),
+            Event::PlanStepSkipped { task_id: Some(task_id), .. } => (task_id, "plan_step_skipped"),
+            _ => return Ok(()),
+        };
+
+        let Some(task) = self.tasks.get_mut(task_id) else {
+            return Err(TaskViolation::NotStarted { task_id: *task_id, event: kind });
+        };
+        if task.finished {
+            return Err(TaskViolation::AfterTerminal { task_id: *task_id, event: kind });
+        }
+
+        match event {
+            Event::TurnComplete { turn_number, .. } => {
+                let previous = task.last_turn.replace(*turn_number);
+                if let Some(previous) = previous.filter(|previous| turn_number <= previous) {
+                    return Err(TaskViolation::TurnOutOfOrder {
+                        task_id: *task_id,
+                        previous,
+                        turn: *turn_number,
+                    });
+                }
+            }
+            Event::TaskComplete { result, .. } => {
+                task.finished = true;
+                if result.task_id != *task_id {
+                    return Err(TaskViolation::ResultMismatch {
+                        task_id: *task_id,
+                        result_task_id: result.task_id,
+                    });
+                }
+            }
+            Event::TaskFailed { .. } | Event::TaskInterrupted { .. } => task.finished = true,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// End the stream, reporting every started task that never finished
+    pub fn finish(self) -> Vec<TaskViolation> {
+        let mut unfinished: Vec<TaskId> = self
+            .tasks
+            .into_iter()
+            .filter(|(_, task)| !task.finished)
+            .map(|(task_id, _)| task_id)
+            .collect();
+        unfinished.sort_by_key(|task_id| task_id.to_string());
+        unfinished.into_iter().map(|task_id| TaskViolation::Unfinished { task_id }).collect()
+    }
+}
+
+/// Check a whole stream, returning every violation in order, followed by
+/// any unfinished tasks
+pub fn validate_task_lifecycle<'a>(
+    events: impl IntoIterator<Item = &'a Event>,
+) -> Vec<TaskViolation> {
+    let mut validator = TaskLifecycleValidator::new();
+    let mut violations: Vec<TaskViolation> = events
+        .into_iter()
+        .filter_map(|event| validator.check(event).err())
+        .collect();
+    violations.extend(validator.finish());
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::{CheckpointId, SubmissionId};
+    use crate::models::{AgentConfig, AgentResult, AgentRole, TaskResult, TokenUsage};
+
+    fn waiting() -> AgentStatus {
+        AgentStatus::Waiting { reason: "approval".into() }
+    }
+
+    fn spawned(agent_id: AgentId) -> Event {
+        Event::AgentSpawned {
+            sub_id: SubmissionId::new(),
+            agent_id,
+            parent_id: None,
+            role: AgentRole::Worker,
+            config: AgentConfig::default(),
+        }
+    }
+
+    fn changed(agent_id: AgentId, status: AgentStatus) -> Event {
+        Event::AgentStatusChanged { sub_id: SubmissionId::new(), agent_id, status }
+    }
+
+    fn complete(agent_id: AgentId) -> Event {
+        Event::AgentComplete {
+            sub_id: SubmissionId::new(),
+            agent_id,
+            result: AgentResult {
+                success: true,
+                summary: "done".into(),
+                files_changed: vec![],
+                output: serde_json::Value::Null,
+            },
+        }
+    }
+
+    fn terminated(agent_id: AgentId) -> Event {
+        Event::AgentTerminated { sub_id: SubmissionId::new(), agent_id, reason: "user".into() }
+    }
+
+    // === Transition Table Tests ===
+
+    #[test]
+    fn test_legal_transitions() {
+        use AgentStatus::*;
+        let legal = [
+            (Spawning, Initializing),
+            (Spawning, Running),
+            (Spawning, Failed),
+            (Initializing, Running),
+            (Initializing, waiting()),
+            (Running, waiting()),
+            (Running, Completed),
+            (waiting(), Running),
+            (waiting(), Waiting { reason: "dependency".into() }),
+            (waiting(), Terminated),
+        ];
+        for (from, to) in legal {
+            assert!(from.can_transition_to(&to), "{from:?} -> {to:?} should be legal");
+        }
+    }
+
+    #[test]
+    fn test_illegal_transitions() {
+        use AgentStatus::*;
+        let illegal = [
+            (Completed, Running),
+            (Terminated, waiting()),
+            (Failed, Failed),
+            (Running, Spawning),
+            (Running, Initializing),
+            (Spawning, waiting()),
+            (Running, Running),
+        ];
+        for (from, to) in illegal {
+            assert!(!from.can_transition_to(&to), "{from:?} -> {to:?} should be illegal");
+        }
+    }
+
+    #[test]
+    fn test_transition_error() {
+        let mut status = AgentStatus::Completed;
+        let err = status.transition(AgentStatus::Running).unwrap_err();
+        let expected = IllegalTransition { from: AgentStatus::Completed, to: AgentStatus::Running };
+        assert_eq!(err, expected);
+        assert_eq!(err.to_string(), "Illegal agent status transition from completed to running");
+        assert_eq!(status, AgentStatus::Completed);
+
+        let mut status = AgentStatus::Spawning;
+        status.transition(AgentStatus::Running).unwrap();
+        assert_eq!(status, AgentStatus::Running);
+    }
+
+    // === Stream Validator Tests ===
+
+    #[test]
+    fn test_valid_stream() {
+        let agent = AgentId::new();
+        let events = [
+            spawned(agent),
+            changed(agent, AgentStatus::Initializing),
+            changed(agent, AgentStatus::Running),
+            changed(agent, waiting()),
+            changed(agent, AgentStatus::Running),
+            complete(agent),
+        ];
+        assert!(validate_agent_lifecycle(&events).is_empty());
+    }
+
+    #[test]
+    fn test_completed_then_running() {
+        let agent = AgentId::new();
+        let events = [
+            spawned(agent),
+            changed(agent, AgentStatus::Running),
+            complete(agent),
+            changed(agent, AgentStatus::Running),
+            changed(agent, waiting()),
+        ];
+        let violations = validate_agent_lifecycle(&events);
+        assert_eq!(
+            violations,
+            [LifecycleViolation::IllegalTransition {
+                agent_id: agent,
+                transition: IllegalTransition {
+                    from: AgentStatus::Completed,
+                    to: AgentStatus::Running,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_terminated_then_waiting() {
+        let agent = AgentId::new();
+        let mut validator = AgentLifecycleValidator::new();
+        validator.check(&spawned(agent)).unwrap();
+        validator.check(&terminated(agent)).unwrap();
+        assert!(matches!(
+            validator.check(&changed(agent, waiting())),
+            Err(LifecycleViolation::IllegalTransition { .. })
+        ));
+    }
+
+    #[test]
+    fn test_not_spawned_and_duplicate_spawn() {
+        let agent = AgentId::new();
+        let violations = validate_agent_lifecycle(&[
+            changed(agent, AgentStatus::Running),
+            spawned(agent),
+            spawned(agent),
+        ]);
+        assert_eq!(
+            violations,
+            [
+                LifecycleViolation::NotSpawned { agent_id: agent, event: "agent_status_changed" },
+                LifecycleViolation::DuplicateSpawn { agent_id: agent },
+            ]
+        );
+    }
+
+    // === Task Validator Tests ===
+
+    fn task_started(task_id: TaskId) -> Event {
+        Event::TaskStarted { sub_id: SubmissionId::new(), task_id, prompt: "go".into() }
+    }
+
+    fn turn(task_id: TaskId, turn_number: u32) -> Event {
+        Event::TurnComplete {
+            sub_id: SubmissionId::new(),
+            task_id,
+            turn_number,
+            checkpoint_id: CheckpointId::new(),
+        }
+    }
+
+    fn task_complete(task_id: TaskId, result_task_id: TaskId) -> Event {
+        Event::TaskComplete {
+            sub_id: SubmissionId::new(),
+            task_id,
+            result: TaskResult {
+                task_id: result_task_id,
+                success: true,
+                summary: "done".into(),
+                files_changed: vec![],
+                token_usage: TokenUsage::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_valid_task_stream() {
+        let task = TaskId::new();
+        let events = [task_started(task), turn(task, 1), turn(task, 2), task_complete(task, task)];
+        assert!(validate_task_lifecycle(&events).is_empty());
+    }
+
+    #[test]
+    fn test_turns_must_increase() {
+        let task = TaskId::new();
+        let violations = validate_task_lifecycle(&[
+            task_started(task),
+            turn(task, 2),
+            turn(task, 2),
+            turn(task, 1),
+            Event::TaskInterrupted { sub_id: SubmissionId::new(), task_id: task },
+        ]);
+        assert_eq!(
+            violations,
+            [
+                TaskViolation::TurnOutOfOrder { task_id: task, previous: 2, turn: 2 },
+                TaskViolation::TurnOutOfOrder { task_id: task, previous: 2, turn: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_events_after_terminal() {
+        let task = TaskId::new();
+        let violations = validate_task_lifecycle(&[
+            task_started(task),
+            Event::TaskFailed { sub_id: SubmissionId::new(), task_id: task, error: "boom".into() },
+            turn(task, 1),
+            task_complete(task, task),
+        ]);
+        assert_eq!(
+            violations,
+            [
+                TaskViolation::AfterTerminal { task_id: task, event: "turn_complete" },
+                TaskViolation::AfterTerminal { task_id: task, event: "task_complete" },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_step_and_usage_after_terminal() {
+        let task = TaskId::new();
+        let stray = TaskId::new();
+        let step_completed = |task_id| Event::PlanStepCompleted {
+            sub_id: SubmissionId::new(),
+            step_id: "1".into(),
+            agent_id: AgentId::new(),
+            task_id,
+        };
+        let violations = validate_task_lifecycle(&[
+            task_started(task),
+            step_completed(task),
+            task_complete(task, task),
+            step_completed(task),
+            Event::UsageUpdate {
+                sub_id: SubmissionId::new(),
+                agent_id: None,
+                model: None,
+                task_id: Some(stray),
+                usage: TokenUsage::default(),
+            },
+        ]);
+        assert_eq!(
+            violations,
+            [
+                TaskViolation::AfterTerminal { task_id: task, event: "plan_step_completed" },
+                TaskViolation::NotStarted { task_id: stray, event: "usage_update" },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_result_task_id_must_match() {
+        let task = TaskId::new();
+        let other = TaskId::new();
+        let violations = validate_task_lifecycle(&[task_started(task), task_complete(task, other)]);
+        let mismatch = TaskViolation::ResultMismatch { task_id: task, result_task_id: other };
+        assert_eq!(violations, [mismatch]);
+    }
+
+    #[test]
+    fn test_unfinished_not_started_and_duplicate() {
+        let task = TaskId::new();
+        let stray = TaskId::new();
+        let violations = validate_task_lifecycle(&[
+            task_started(task),
+            task_started(task),
+            turn(stray, 1),
+        ]);
+        assert_eq!(
+            violations,
+            [
+                TaskViolation::DuplicateStart { task_id: task },
+                TaskViolation::NotStarted { task_id: stray, event: "turn_complete" },
+                TaskViolation::Unfinished { task_id: task },
+            ]
+        );
+    }
+}
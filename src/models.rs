@@ -11,6 +11,7 @@ use crate::ids::*;
 
 /// Configuration for a Goblin session
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SessionConfig {
     /// Working directory for agents
     #[serde(default)]
@@ -27,12 +28,18 @@ pub struct SessionConfig {
     /// Approval mode
     #[serde(default)]
     pub approval_mode: ApprovalMode,
+    /// Rules used when `approval_mode` is `custom`
+    #[serde(default)]
+    pub approval_policy: Option<ApprovalPolicy>,
     /// Sandbox policy
     #[serde(default)]
     pub sandbox: SandboxConfig,
     /// Max parallel agents
     #[serde(default = "default_max_agents")]
     pub max_parallel_agents: usize,
+    /// Token budget for the whole session, across all agents
+    #[serde(default)]
+    pub token_budget: Option<u64>,
 }
 
 fn default_max_agents() -> usize {
@@ -41,6 +48,7 @@ fn default_max_agents() -> usize {
 
 /// Session runtime settings (modifiable without reconfigure)
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SessionSettings {
     /// Show rate limit info in UI
     #[serde(default)]
@@ -55,6 +63,7 @@ pub struct SessionSettings {
 
 /// Approval mode for tool execution
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum ApprovalMode {
     /// Always require approval
@@ -64,12 +73,86 @@ pub enum ApprovalMode {
     /// Require approval based on risk level
     #[default]
     RiskBased,
-    /// Custom rules (defined in policy)
+    /// Custom rules (defined in `approval_policy`)
     Custom,
 }
 
+/// Ordered approval rules; the first matching rule decides
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ApprovalPolicy {
+    /// Rules, checked in order
+    #[serde(default)]
+    pub rules: Vec<ApprovalRule>,
+    /// Action when no rule matches
+    #[serde(default)]
+    pub default_action: ApprovalAction,
+}
+
+/// One approval rule; every condition that is set must match
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ApprovalRule {
+    /// Name reported when this rule decides
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Tool name patterns (`*` matches any run of characters, `/` included);
+    /// empty matches any tool
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Conditions on the tool call arguments
+    #[serde(default)]
+    pub arguments: Vec<ArgumentCondition>,
+    /// Lowest risk level this rule applies to
+    #[serde(default)]
+    pub min_risk: Option<RiskLevel>,
+    /// Highest risk level this rule applies to
+    #[serde(default)]
+    pub max_risk: Option<RiskLevel>,
+    /// Agent roles this rule applies to; empty matches any role
+    #[serde(default)]
+    pub roles: Vec<AgentRole>,
+    /// Path glob the session working directory must match (`*` stops at `/`,
+    /// `**` does not)
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// What to do when the rule matches
+    pub action: ApprovalAction,
+}
+
+/// Condition on a tool argument addressed by a JSON pointer (e.g. `/command`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ArgumentCondition {
+    /// Value is present
+    Exists { pointer: String },
+    /// Value is missing
+    Absent { pointer: String },
+    /// Value equals `value`
+    Equals { pointer: String, value: serde_json::Value },
+    /// Value is a string matching `pattern`, where `*` matches any run of
+    /// characters, `/` included
+    Matches { pointer: String, pattern: String },
+}
+
+/// Outcome of an approval rule
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalAction {
+    /// Run without asking
+    Allow,
+    /// Refuse the call
+    Deny,
+    /// Ask the user
+    #[default]
+    Ask,
+}
+
 /// Sandbox configuration
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SandboxConfig {
     /// Enable sandboxing
     #[serde(default = "default_true")]
@@ -80,6 +163,9 @@ pub struct SandboxConfig {
     /// Additional writable paths
     #[serde(default)]
     pub writable_paths: Vec<PathBuf>,
+    /// Filesystem read/write rules
+    #[serde(default)]
+    pub filesystem: FilesystemPolicy,
     /// Execution timeout
     #[serde(default)]
     pub timeout_secs: Option<u64>,
@@ -89,8 +175,47 @@ fn default_true() -> bool {
     true
 }
 
+/// Filesystem access rules for sandbox
+///
+/// Relative roots are resolved against the session `cwd` and `~` against
+/// the home directory. Deny globs win over every root; a glob without a
+/// leading `/` or `~/` matches at any depth, so `.env` denies every `.env`
+/// file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FilesystemPolicy {
+    /// Roots agents may read; empty allows reading anywhere
+    #[serde(default)]
+    pub readable_roots: Vec<PathBuf>,
+    /// Roots agents may write (also readable)
+    #[serde(default)]
+    pub writable_roots: Vec<PathBuf>,
+    /// Whether the session `cwd` is a writable root
+    #[serde(default = "default_true")]
+    pub cwd_writable: bool,
+    /// Globs that may be neither read nor written
+    #[serde(default = "default_deny")]
+    pub deny: Vec<String>,
+}
+
+impl Default for FilesystemPolicy {
+    fn default() -> Self {
+        Self {
+            readable_roots: Vec::new(),
+            writable_roots: Vec::new(),
+            cwd_writable: true,
+            deny: default_deny(),
+        }
+    }
+}
+
+fn default_deny() -> Vec<String> {
+    ["~/.ssh", "~/.aws", "~/.gnupg", ".env"].map(String::from).to_vec()
+}
+
 /// Network access policy for sandbox
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum NetworkPolicy {
     /// No network access
@@ -98,16 +223,44 @@ pub enum NetworkPolicy {
     None,
     /// Localhost only
     Localhost,
-    /// Specific hosts allowed
-    Allowlist(Vec<String>),
+    /// Specific hosts allowed: `host`, `*.domain`, IP or CIDR, each with an optional `:port`
+    Allowlist(#[serde(deserialize_with = "crate::network::deserialize_allowlist")] Vec<String>),
     /// Full network access
     Full,
 }
 
+// === Handshake Types ===
+
+/// Optional protocol feature negotiated during the handshake
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Incremental `AgentMessage` content deltas
+    StreamingDeltas,
+    /// Incremental hierarchy patches instead of full snapshots
+    HierarchyPatches,
+    /// Binary blob attachments
+    BlobAttachments,
+    /// Capability not known to this version of the protocol
+    #[serde(other)]
+    Unknown,
+}
+
+impl Capability {
+    /// All capabilities known to this version of the protocol
+    pub const ALL: &'static [Capability] = &[
+        Capability::StreamingDeltas,
+        Capability::HierarchyPatches,
+        Capability::BlobAttachments,
+    ];
+}
+
 // === MCP Configuration ===
 
 /// Configuration for an MCP server
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct McpServerConfig {
     /// Unique identifier for this server
     pub id: String,
@@ -122,6 +275,7 @@ pub struct McpServerConfig {
 
 /// MCP transport configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum McpTransport {
     /// stdio-based transport
@@ -143,7 +297,8 @@ pub enum McpTransport {
 // === Agent Types ===
 
 /// Role of an agent in the hierarchy
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum AgentRole {
     /// Top-level orchestrator
@@ -170,6 +325,7 @@ impl Default for AgentRole {
 
 /// Configuration for spawning an agent
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AgentConfig {
     /// Agent role
     #[serde(default)]
@@ -199,6 +355,7 @@ pub struct AgentConfig {
 
 /// Current status of an agent
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum AgentStatus {
     /// Being created
@@ -223,8 +380,24 @@ impl Default for AgentStatus {
     }
 }
 
+impl AgentStatus {
+    /// Wire name of the status, without any payload
+    pub fn name(&self) -> &'static str {
+        match self {
+            AgentStatus::Spawning => "spawning",
+            AgentStatus::Initializing => "initializing",
+            AgentStatus::Running => "running",
+            AgentStatus::Waiting { .. } => "waiting",
+            AgentStatus::Completed => "completed",
+            AgentStatus::Failed => "failed",
+            AgentStatus::Terminated => "terminated",
+        }
+    }
+}
+
 /// Result from an agent completing its task
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AgentResult {
     /// Success or failure
     pub success: bool,
@@ -242,6 +415,7 @@ pub struct AgentResult {
 
 /// Context provided with a task
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TaskContext {
     /// Current working directory
     #[serde(default)]
@@ -259,6 +433,7 @@ pub struct TaskContext {
 
 /// Task assigned to an agent
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TaskAssignment {
     /// Task ID
     pub task_id: TaskId,
@@ -277,6 +452,7 @@ pub struct TaskAssignment {
 
 /// Result of a completed task
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TaskResult {
     /// Task ID
     pub task_id: TaskId,
@@ -296,6 +472,7 @@ pub struct TaskResult {
 
 /// Output from a tool execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ToolOutput {
     /// Success or failure
     pub success: bool,
@@ -309,8 +486,9 @@ pub struct ToolOutput {
     pub exit_code: Option<i32>,
 }
 
-/// Risk level for tool execution
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Risk level for tool execution, ordered from least to most risky
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum RiskLevel {
     /// No risk (read-only)
@@ -335,6 +513,7 @@ impl Default for RiskLevel {
 
 /// Granularity of task planning
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum PlanGranularity {
     /// High-level steps only
@@ -348,6 +527,7 @@ pub enum PlanGranularity {
 
 /// A task decomposition plan
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TaskPlan {
     /// Original request
     pub original_request: String,
@@ -363,7 +543,8 @@ pub struct TaskPlan {
 }
 
 /// A single step in a plan
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct PlanStep {
     /// Step ID
     pub id: String,
@@ -374,10 +555,14 @@ pub struct PlanStep {
     /// Estimated complexity
     #[serde(default)]
     pub complexity: StepComplexity,
+    /// Estimated token usage for this step
+    #[serde(default)]
+    pub estimated_tokens: Option<u64>,
 }
 
 /// Complexity of a plan step
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum StepComplexity {
     /// Simple, single action
@@ -389,10 +574,36 @@ pub enum StepComplexity {
     Complex,
 }
 
+/// Step-level differences between two versions of a plan
+///
+/// Steps are matched by id.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlanDiff {
+    /// Steps only in the new plan, in new plan order
+    #[serde(default)]
+    pub added: Vec<PlanStep>,
+    /// Steps only in the old plan, in old plan order
+    #[serde(default)]
+    pub removed: Vec<PlanStep>,
+    /// Steps in both plans whose contents differ, in new plan order
+    #[serde(default)]
+    pub changed: Vec<PlanStepChange>,
+}
+
+/// A plan step before and after a revision
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlanStepChange {
+    pub before: PlanStep,
+    pub after: PlanStep,
+}
+
 // === Hierarchy Types ===
 
 /// Tree representation of agent hierarchy
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct AgentTree {
     /// Agent info
     pub agent_id: AgentId,
@@ -404,10 +615,42 @@ pub struct AgentTree {
     pub children: Vec<AgentTree>,
 }
 
+/// A single change to an `AgentTree`, sent in `Event::HierarchyPatch`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HierarchyOp {
+    /// Replace the whole tree
+    ReplaceRoot {
+        root: AgentTree,
+    },
+    /// Insert a subtree under `parent_id` at position `index`
+    InsertChild {
+        parent_id: AgentId,
+        index: usize,
+        node: AgentTree,
+    },
+    /// Remove an agent and all of its descendants
+    RemoveSubtree {
+        agent_id: AgentId,
+    },
+    /// Change an agent's status
+    UpdateStatus {
+        agent_id: AgentId,
+        status: AgentStatus,
+    },
+    /// Change an agent's task summary
+    UpdateTaskSummary {
+        agent_id: AgentId,
+        task_summary: Option<String>,
+    },
+}
+
 // === Checkpoint Types ===
 
 /// Metadata for a checkpoint
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CheckpointMeta {
     /// Checkpoint ID
     pub id: CheckpointId,
@@ -427,11 +670,18 @@ pub struct CheckpointMeta {
 
 /// Token usage statistics
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TokenUsage {
     /// Input tokens
     pub input_tokens: u64,
     /// Output tokens
     pub output_tokens: u64,
+    /// Input tokens read from the prompt cache, not counted in `input_tokens`
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    /// Input tokens written to the prompt cache, not counted in `input_tokens`
+    #[serde(default)]
+    pub cache_write_tokens: u64,
     /// Total tokens
     pub total_tokens: u64,
     /// Estimated cost (USD)
@@ -439,10 +689,29 @@ pub struct TokenUsage {
     pub estimated_cost_usd: Option<f64>,
 }
 
+impl TokenUsage {
+    /// Add another usage record to this one
+    ///
+    /// The cost stays `None` only if neither side has one. Token counts
+    /// saturate at `u64::MAX`.
+    pub fn accumulate(&mut self, other: &TokenUsage) {
+        self.input_tokens = self.input_tokens.saturating_add(other.input_tokens);
+        self.output_tokens = self.output_tokens.saturating_add(other.output_tokens);
+        self.cache_read_tokens = self.cache_read_tokens.saturating_add(other.cache_read_tokens);
+        self.cache_write_tokens = self.cache_write_tokens.saturating_add(other.cache_write_tokens);
+        self.total_tokens = self.total_tokens.saturating_add(other.total_tokens);
+        self.estimated_cost_usd = match (self.estimated_cost_usd, other.estimated_cost_usd) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0.0) + b.unwrap_or(0.0)),
+        };
+    }
+}
+
 // === Message Types ===
 
 /// Type of message from an agent
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "snake_case")]
 pub enum MessageType {
     /// Regular text response
@@ -469,6 +738,7 @@ impl Default for MessageType {
 
 /// Image attached to a prompt
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ImageAttachment {
     /// Base64 encoded image data
     pub data: String,
@@ -565,6 +835,7 @@ mod tests {
             enabled: true,
             network: NetworkPolicy::Localhost,
             writable_paths: vec![PathBuf::from("/tmp")],
+            filesystem: FilesystemPolicy::default(),
             timeout_secs: Some(120),
         };
         
@@ -737,6 +1008,24 @@ mod tests {
         assert_eq!(status, AgentStatus::Spawning);
     }
 
+    #[test]
+    fn test_agent_status_name_matches_wire() {
+        let statuses = [
+            AgentStatus::Spawning,
+            AgentStatus::Running,
+            AgentStatus::Waiting { reason: "approval".into() },
+            AgentStatus::Terminated,
+        ];
+        for status in statuses {
+            let json = serde_json::to_value(&status).unwrap();
+            let tag = match &json {
+                serde_json::Value::String(tag) => tag.clone(),
+                other => other.as_object().unwrap().keys().next().unwrap().clone(),
+            };
+            assert_eq!(status.name(), tag);
+        }
+    }
+
     // === AgentResult Tests ===
 
     #[test]
@@ -811,6 +1100,7 @@ mod tests {
                 output_tokens: 2000,
                 total_tokens: 7000,
                 estimated_cost_usd: Some(0.07),
+                ..Default::default()
             },
         };
         
@@ -894,12 +1184,14 @@ mod tests {
                     description: "Create auth module".into(),
                     expected_outcome: "Working auth".into(),
                     complexity: StepComplexity::Complex,
+                    estimated_tokens: None,
                 },
                 PlanStep {
                     id: "2".into(),
                     description: "Add tests".into(),
                     expected_outcome: "Tests passing".into(),
                     complexity: StepComplexity::Moderate,
+                    estimated_tokens: None,
                 },
             ],
             agent_assignments: [("1".into(), AgentRole::Worker)].into(),
@@ -1005,6 +1297,7 @@ mod tests {
             output_tokens: 5_000,
             total_tokens: 15_000,
             estimated_cost_usd: Some(0.15),
+            ..Default::default()
         };
         
         let json = serde_json::to_string(&usage).unwrap();
@@ -1012,6 +1305,30 @@ mod tests {
         assert!(json.contains("0.15"));
     }
 
+    #[test]
+    fn test_token_usage_accumulate() {
+        let mut usage = TokenUsage::default();
+        usage.accumulate(&TokenUsage {
+            input_tokens: 10,
+            output_tokens: 5,
+            total_tokens: 15,
+            ..Default::default()
+        });
+        assert!(usage.estimated_cost_usd.is_none());
+
+        usage.accumulate(&TokenUsage {
+            input_tokens: 1,
+            output_tokens: 1,
+            cache_read_tokens: 40,
+            cache_write_tokens: 0,
+            total_tokens: 2,
+            estimated_cost_usd: Some(0.5),
+        });
+        assert_eq!(usage.total_tokens, 17);
+        assert_eq!(usage.cache_read_tokens, 40);
+        assert_eq!(usage.estimated_cost_usd, Some(0.5));
+    }
+
     // === MessageType Tests ===
 
     #[test]
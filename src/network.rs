@@ -0,0 +1,365 @@
+//! Matching destinations against a `NetworkPolicy`
+//!
+//! Allowlist entries take one of these forms, each optionally followed by
+//! `:port` (IPv6 addresses and blocks need brackets for that, as in
+//! `[::1]:8080` or `[fd00::/8]:443`):
+//!
+//! - `api.example.com` — that host only, case-insensitive
+//! - `*.example.com` — any subdomain, at any depth, but not `example.com`
+//! - `10.1.2.3`, `::1` — that address
+//! - `10.0.0.0/8`, `fd00::/8` — any address in the block
+//!
+//! Names only match names and addresses only match addresses; nothing is
+//! resolved. IPv4-mapped IPv6 addresses match as their IPv4 form.
+//!
+//! `NetworkPolicy::Localhost` allows the name `localhost` (and
+//! `*.localhost`), `127.0.0.0/8` and `::1` on any port. An allowlist entry
+//! of `localhost` means the same set of destinations, restricted to its port
+//! if it has one. `Allowlist` never allows loopback implicitly.
+//!
+//! ```
+//! use warhorn::NetworkPolicy;
+//!
+//! let policy = NetworkPolicy::Allowlist(vec!["*.github.com:443".into(), "10.0.0.0/8".into()]);
+//! assert!(policy.allows("api.github.com", 443));
+//! assert!(!policy.allows("github.com", 443));
+//! assert!(policy.allows("10.20.30.40", 5432));
+//! ```
+
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::error::ProtocolError;
+use crate::models::NetworkPolicy;
+
+/// Host part of an allowlist entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostPattern {
+    /// A single host name, lowercased without a trailing dot
+    Name(String),
+    /// Subdomains of this name (`*.example.com` stores `example.com`)
+    Wildcard(String),
+    /// A single address
+    Ip(IpAddr),
+    /// An address block; host bits of `network` are zero
+    Cidr { network: IpAddr, prefix: u8 },
+}
+
+/// One parsed allowlist entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AllowlistEntry {
+    pub host: HostPattern,
+    /// Port restriction; `None` allows every port
+    pub port: Option<u16>,
+}
+
+impl FromStr for AllowlistEntry {
+    type Err = ProtocolError;
+
+    fn from_str(entry: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| ProtocolError::InvalidAllowlistEntry {
+            entry: entry.to_string(),
+            reason: reason.to_string(),
+        };
+        if entry.is_empty() || entry.contains(char::is_whitespace) {
+            return Err(invalid("entries must be non-empty and contain no whitespace"));
+        }
+
+        let (host, port) = if let Some(bracketed) = entry.strip_prefix('[') {
+            let (inner, rest) = bracketed.split_once(']').ok_or_else(|| invalid("unclosed '['"))?;
+            let port = match rest {
+                "" => None,
+                _ => Some(
+                    rest.strip_prefix(':').ok_or_else(|| invalid("expected ':port' after ']'"))?,
+                ),
+            };
+            if !inner.contains(':') {
+                return Err(invalid("brackets are only for IPv6 addresses"));
+            }
+            (inner, port)
+        } else if entry.matches(':').count() > 1 {
+            (entry, None)
+        } else {
+            match entry.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (entry, None),
+            }
+        };
+
+        let port = port
+            .map(|port| port.parse::<u16>().ok().filter(|port| *port != 0))
+            .map(|port| port.ok_or_else(|| invalid("port must be a number from 1 to 65535")))
+            .transpose()?;
+
+        let host = if let Some((address, prefix)) = host.split_once('/') {
+            let address: IpAddr =
+                address.parse().map_err(|_| invalid("CIDR block needs an IP address"))?;
+            let address = canonical(address);
+            let max = if address.is_ipv4() { 32 } else { 128 };
+            let prefix = prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|prefix| *prefix <= max)
+                .ok_or_else(|| invalid("CIDR prefix is out of range"))?;
+            HostPattern::Cidr { network: mask(address, prefix), prefix }
+        } else if let Ok(address) = host.parse::<IpAddr>() {
+            HostPattern::Ip(canonical(address))
+        } else if let Some(domain) = host.strip_prefix("*.") {
+            let domain = hostname(domain).ok_or_else(|| invalid("invalid domain after '*.'"))?;
+            HostPattern::Wildcard(domain)
+        } else {
+            HostPattern::Name(hostname(host).ok_or_else(|| invalid("invalid host name"))?)
+        };
+        Ok(AllowlistEntry { host, port })
+    }
+}
+
+impl AllowlistEntry {
+    /// Check whether a destination is covered by this entry
+    pub fn matches(&self, host: &str, port: u16) -> bool {
+        if self.port.is_some_and(|allowed| allowed != port) {
+            return false;
+        }
+        let destination = Destination::parse(host);
+        match (&self.host, &destination) {
+            (HostPattern::Name(name), _) if name == "localhost" => destination.is_localhost(),
+            (HostPattern::Name(name), Destination::Name(host)) => name == host,
+            (HostPattern::Wildcard(domain), Destination::Name(host)) => host
+                .strip_suffix(domain.as_str())
+                .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.')),
+            (HostPattern::Ip(address), Destination::Ip(host)) => address == host,
+            (HostPattern::Cidr { network, prefix }, Destination::Ip(host)) => {
+                network.is_ipv4() == host.is_ipv4() && mask(*host, *prefix) == *network
+            }
+            _ => false,
+        }
+    }
+}
+
+impl NetworkPolicy {
+    /// Check whether the policy allows connecting to `host` on `port`
+    ///
+    /// `host` may be a name, an IP address, or a bracketed IPv6 address.
+    /// Allowlist entries that do not parse never match.
+    pub fn allows(&self, host: &str, port: u16) -> bool {
+        match self {
+            NetworkPolicy::None => false,
+            NetworkPolicy::Full => true,
+            NetworkPolicy::Localhost => Destination::parse(host).is_localhost(),
+            NetworkPolicy::Allowlist(entries) => entries
+                .iter()
+                .filter_map(|entry| entry.parse::<AllowlistEntry>().ok())
+                .any(|entry| entry.matches(host, port)),
+        }
+    }
+
+    /// Check that every allowlist entry parses
+    pub fn validate(&self) -> Result<(), ProtocolError> {
+        if let NetworkPolicy::Allowlist(entries) = self {
+            for entry in entries {
+                entry.parse::<AllowlistEntry>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reject malformed allowlist entries while deserializing a `NetworkPolicy`
+pub(crate) fn deserialize_allowlist<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let entries = Vec::<String>::deserialize(deserializer)?;
+    for entry in &entries {
+        entry.parse::<AllowlistEntry>().map_err(serde::de::Error::custom)?;
+    }
+    Ok(entries)
+}
+
+enum Destination {
+    Name(String),
+    Ip(IpAddr),
+}
+
+impl Destination {
+    fn parse(host: &str) -> Self {
+        let host = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host);
+        match host.parse::<IpAddr>() {
+            Ok(address) => Destination::Ip(canonical(address)),
+            Err(_) => Destination::Name(host.trim_end_matches('.').to_ascii_lowercase()),
+        }
+    }
+
+    fn is_localhost(&self) -> bool {
+        match self {
+            Destination::Name(name) => name == "localhost" || name.ends_with(".localhost"),
+            Destination::Ip(address) => address.is_loopback(),
+        }
+    }
+}
+
+fn canonical(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(address, IpAddr::V4),
+        IpAddr::V4(_) => address,
+    }
+}
+
+fn mask(address: IpAddr, prefix: u8) -> IpAddr {
+    match address {
+        IpAddr::V4(v4) => {
+            let bits = u32::from(v4) & u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            IpAddr::V4(bits.into())
+        }
+        IpAddr::V6(v6) => {
+            let bits = u128::from(v6) & u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            IpAddr::V6(bits.into())
+        }
+    }
+}
+
+/// Lowercase and check a host name: dot-separated labels of letters,
+/// digits, `-` and `_`, not starting or ending with `-`
+fn hostname(host: &str) -> Option<String> {
+    let host = host.strip_suffix('.').unwrap_or(host).to_ascii_lowercase();
+    let valid = !host.is_empty()
+        && host.len() <= 253
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        });
+    valid.then_some(host)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SandboxConfig;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    fn entry(text: &str) -> AllowlistEntry {
+        text.parse().unwrap_or_else(|e| panic!("{text}: {e}"))
+    }
+
+    fn allowlist(entries: &[&str]) -> NetworkPolicy {
+        NetworkPolicy::Allowlist(entries.iter().map(|e| e.to_string()).collect())
+    }
+
+    // === Parsing Tests ===
+
+    #[test]
+    fn test_parse_entries() {
+        assert_eq!(entry("API.Example.com.").host, HostPattern::Name("api.example.com".into()));
+        assert_eq!(entry("*.example.com:443"), AllowlistEntry {
+            host: HostPattern::Wildcard("example.com".into()),
+            port: Some(443),
+        });
+        assert_eq!(entry("10.1.2.3:5432").host, HostPattern::Ip(Ipv4Addr::new(10, 1, 2, 3).into()));
+        assert_eq!(entry("::1").host, HostPattern::Ip(Ipv6Addr::LOCALHOST.into()));
+        assert_eq!(entry("[::1]:8080").port, Some(8080));
+        assert_eq!(entry("10.9.8.7/8").host, HostPattern::Cidr {
+            network: Ipv4Addr::new(10, 0, 0, 0).into(),
+            prefix: 8,
+        });
+        assert_eq!(entry("[fd00::/8]:443").port, Some(443));
+    }
+
+    #[test]
+    fn test_reject_malformed_entries() {
+        let malformed = [
+            "",
+            "a b",
+            "*",
+            "*.",
+            "host:0",
+            "host:http",
+            "-bad.com",
+            "a..b",
+            "10.0.0.0/33",
+            "fd00::/129",
+            "[1.2.3.4]",
+            "[::1",
+            "[::1]8080",
+            "x/8",
+        ];
+        for text in malformed {
+            assert!(text.parse::<AllowlistEntry>().is_err(), "{text:?} should be rejected");
+        }
+    }
+
+    // === Matching Tests ===
+
+    #[test]
+    fn test_names_and_wildcards() {
+        let policy = allowlist(&["api.example.com", "*.github.com:443"]);
+        assert!(policy.allows("API.example.com.", 80));
+        assert!(!policy.allows("www.example.com", 80));
+        assert!(policy.allows("api.github.com", 443));
+        assert!(policy.allows("a.b.github.com", 443));
+        assert!(!policy.allows("github.com", 443));
+        assert!(!policy.allows("evilgithub.com", 443));
+        assert!(!policy.allows("api.github.com", 80));
+    }
+
+    #[test]
+    fn test_addresses_and_cidr() {
+        let policy = allowlist(&["10.0.0.0/8", "192.168.1.5:22", "2001:db8::/32"]);
+        assert!(policy.allows("10.255.0.1", 1));
+        assert!(policy.allows("::ffff:10.1.1.1", 1));
+        assert!(!policy.allows("11.0.0.1", 1));
+        assert!(policy.allows("192.168.1.5", 22));
+        assert!(!policy.allows("192.168.1.5", 23));
+        assert!(policy.allows("[2001:db8::1]", 443));
+        assert!(!policy.allows("2001:db9::1", 443));
+        assert!(!policy.allows("localhost", 80));
+    }
+
+    #[test]
+    fn test_localhost() {
+        let policy = NetworkPolicy::Localhost;
+        let local = [
+            "localhost",
+            "LOCALHOST",
+            "app.localhost",
+            "127.0.0.1",
+            "127.8.9.10",
+            "::1",
+            "[::1]",
+            "::ffff:127.0.0.1",
+        ];
+        for host in local {
+            assert!(policy.allows(host, 3000), "{host}");
+        }
+        for host in ["10.0.0.1", "localhost.example.com", "::2"] {
+            assert!(!policy.allows(host, 3000), "{host}");
+        }
+
+        let only_dev_server = allowlist(&["localhost:3000"]);
+        assert!(only_dev_server.allows("127.0.0.1", 3000));
+        assert!(only_dev_server.allows("::1", 3000));
+        assert!(!only_dev_server.allows("127.0.0.1", 5432));
+
+        assert!(!NetworkPolicy::None.allows("localhost", 80));
+        assert!(NetworkPolicy::Full.allows("example.org", 80));
+    }
+
+    // === Deserialization Tests ===
+
+    #[test]
+    fn test_sandbox_config_rejects_malformed_allowlist() {
+        let ok = r#"{ "network": { "allowlist": ["*.example.com", "[::1]:8080"] } }"#;
+        let config: SandboxConfig = serde_json::from_str(ok).unwrap();
+        assert!(config.network.validate().is_ok());
+
+        let bad = r#"{ "network": { "allowlist": ["example.com:99999"] } }"#;
+        let err = serde_json::from_str::<SandboxConfig>(bad).unwrap_err();
+        assert!(err.to_string().contains("example.com:99999"), "{err}");
+
+        assert!(allowlist(&["*"]).validate().is_err());
+    }
+}
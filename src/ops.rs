@@ -4,6 +4,7 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::envelope::ProtocolVersion;
 use crate::ids::*;
 use crate::models::*;
 
@@ -11,9 +12,23 @@ use crate::models::*;
 ///
 /// Each operation has an associated `SubmissionId` for correlation with events.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[non_exhaustive]
 pub enum Op {
+    /// Open the connection and negotiate version and capabilities
+    Hello {
+        /// Submission ID for correlation
+        sub_id: SubmissionId,
+        /// Name of the connecting client
+        client_name: String,
+        /// Highest protocol version the client speaks
+        protocol_version: ProtocolVersion,
+        /// Capabilities the client supports
+        #[serde(default)]
+        capabilities: Vec<Capability>,
+    },
+
     /// Configure or reconfigure the session
     ConfigureSession {
         /// Submission ID for correlation
@@ -143,6 +158,29 @@ pub enum Op {
         granularity: PlanGranularity,
     },
 
+    /// Approve the current plan and start executing it
+    ApprovePlan {
+        /// Submission ID for correlation
+        sub_id: SubmissionId,
+    },
+
+    /// Reject the current plan and ask for a new one
+    RejectPlan {
+        /// Submission ID for correlation
+        sub_id: SubmissionId,
+        /// What the new plan should do differently
+        #[serde(default)]
+        feedback: Option<String>,
+    },
+
+    /// Replace the current plan with an edited one
+    RevisePlan {
+        /// Submission ID for correlation
+        sub_id: SubmissionId,
+        /// The edited plan
+        plan: TaskPlan,
+    },
+
     /// Update session settings
     UpdateSettings {
         /// Submission ID for correlation
@@ -156,6 +194,7 @@ impl Op {
     /// Get the submission ID for this operation
     pub fn sub_id(&self) -> &SubmissionId {
         match self {
+            Op::Hello { sub_id, .. } => sub_id,
             Op::ConfigureSession { sub_id, .. } => sub_id,
             Op::UserInput { sub_id, .. } => sub_id,
             Op::Interrupt { sub_id, .. } => sub_id,
@@ -169,10 +208,23 @@ impl Op {
             Op::ListCheckpoints { sub_id, .. } => sub_id,
             Op::Undo { sub_id, .. } => sub_id,
             Op::TogglePlanMode { sub_id, .. } => sub_id,
+            Op::ApprovePlan { sub_id, .. } => sub_id,
+            Op::RejectPlan { sub_id, .. } => sub_id,
+            Op::RevisePlan { sub_id, .. } => sub_id,
             Op::UpdateSettings { sub_id, .. } => sub_id,
         }
     }
 
+    /// Create a Hello operation for the current protocol version
+    pub fn hello(client_name: impl Into<String>, capabilities: Vec<Capability>) -> Self {
+        Op::Hello {
+            sub_id: SubmissionId::new(),
+            client_name: client_name.into(),
+            protocol_version: ProtocolVersion::current(),
+            capabilities,
+        }
+    }
+
     /// Create a UserInput operation
     pub fn user_input(prompt: impl Into<String>) -> Self {
         Op::UserInput {
@@ -192,6 +244,24 @@ impl Op {
         }
     }
 
+    /// Create an ApprovePlan operation
+    pub fn approve_plan() -> Self {
+        Op::ApprovePlan { sub_id: SubmissionId::new() }
+    }
+
+    /// Create a RejectPlan operation
+    pub fn reject_plan(feedback: Option<impl Into<String>>) -> Self {
+        Op::RejectPlan {
+            sub_id: SubmissionId::new(),
+            feedback: feedback.map(Into::into),
+        }
+    }
+
+    /// Create a RevisePlan operation
+    pub fn revise_plan(plan: TaskPlan) -> Self {
+        Op::RevisePlan { sub_id: SubmissionId::new(), plan }
+    }
+
     /// Create an ExecApproval operation
     pub fn approve_exec(call_id: CallId) -> Self {
         Op::ExecApproval {
@@ -217,6 +287,44 @@ impl Op {
 mod tests {
     use super::*;
 
+    // === Hello Operation Tests ===
+
+    #[test]
+    fn test_hello_serialization() {
+        let op = Op::hello("lair", vec![Capability::StreamingDeltas]);
+        let json = serde_json::to_string(&op).unwrap();
+        assert!(json.contains("\"type\":\"hello\""));
+        assert!(json.contains("streaming_deltas"));
+
+        let parsed: Op = serde_json::from_str(&json).unwrap();
+        match parsed {
+            Op::Hello { client_name, protocol_version, capabilities, .. } => {
+                assert_eq!(client_name, "lair");
+                assert_eq!(protocol_version, ProtocolVersion::current());
+                assert_eq!(capabilities, vec![Capability::StreamingDeltas]);
+            }
+            _ => panic!("Wrong variant"),
+        }
+    }
+
+    #[test]
+    fn test_hello_unknown_capability() {
+        let json = r#"{
+            "type": "hello",
+            "sub_id": "s1",
+            "client_name": "lair-next",
+            "protocol_version": "0.9.0",
+            "capabilities": ["streaming_deltas", "telepathy"]
+        }"#;
+        let parsed: Op = serde_json::from_str(json).unwrap();
+        match parsed {
+            Op::Hello { capabilities, .. } => {
+                assert_eq!(capabilities, vec![Capability::StreamingDeltas, Capability::Unknown]);
+            }
+            _ => panic!("Wrong variant"),
+        }
+    }
+
     // === UserInput Operation Tests ===
 
     #[test]
@@ -442,6 +550,32 @@ mod tests {
         assert!(json.contains("detailed"));
     }
 
+    #[test]
+    fn test_plan_review_ops() {
+        let json = serde_json::to_string(&Op::approve_plan()).unwrap();
+        assert!(json.contains(r#""type":"approve_plan""#));
+
+        let json = serde_json::to_string(&Op::reject_plan(Some("Too many steps"))).unwrap();
+        assert!(json.contains(r#""type":"reject_plan""#));
+        assert!(json.contains("Too many steps"));
+        assert!(matches!(Op::reject_plan(None::<String>), Op::RejectPlan { feedback: None, .. }));
+
+        let plan = TaskPlan {
+            original_request: "Add auth".into(),
+            steps: vec![],
+            agent_assignments: Default::default(),
+            dependencies: vec![],
+            estimated_tokens: 0,
+        };
+        let json = serde_json::to_string(&Op::revise_plan(plan)).unwrap();
+        assert!(json.contains(r#""type":"revise_plan""#));
+        assert!(json.contains("Add auth"));
+
+        let json = r#"{"type":"reject_plan","sub_id":"s"}"#;
+        let parsed: Op = serde_json::from_str(json).unwrap();
+        assert!(matches!(parsed, Op::RejectPlan { feedback: None, .. }));
+    }
+
     // === Update Settings Tests ===
 
     #[test]
@@ -499,6 +633,7 @@ mod tests {
     fn test_sub_id_extraction_all_variants() {
         // Test that sub_id() works for all variants
         let ops = vec![
+            Op::hello("test", vec![]),
             Op::user_input("test"),
             Op::interrupt(),
             Op::approve_exec(CallId::new()),
@@ -0,0 +1,698 @@
+//! Scheduling a `TaskPlan`
+//!
+//! A plan's steps and `dependencies` form a graph where `(a, b)` means step
+//! `a` must finish before step `b` starts. [`TaskPlan::validate`] checks that
+//! the graph is a DAG over known, unique step ids; the other methods here
+//! order it for scheduling. Ties are always broken by position in `steps`,
+//! so results are deterministic. [`PlanProgress`] follows the `PlanStep*`
+//! events to check steps off as agents finish them.
+//!
+//! ```
+//! use warhorn::plan::StepWeight;
+//! use warhorn::{PlanStep, StepComplexity, TaskPlan};
+//!
+//! let step = |id: &str, complexity| PlanStep {
+//!     id: id.into(),
+//!     description: id.into(),
+//!     expected_outcome: String::new(),
+//!     complexity,
+//!     estimated_tokens: None,
+//! };
+//! let plan = TaskPlan {
+//!     original_request: "Add auth".into(),
+//!     steps: vec![
+//!         step("schema", StepComplexity::Simple),
+//!         step("api", StepComplexity::Complex),
+//!         step("ui", StepComplexity::Moderate),
+//!     ],
+//!     agent_assignments: Default::default(),
+//!     dependencies: vec![("schema".into(), "api".into()), ("schema".into(), "ui".into())],
+//!     estimated_tokens: 0,
+//! };
+//!
+//! let waves: Vec<Vec<&str>> = plan
+//!     .waves()
+//!     .unwrap()
+//!     .iter()
+//!     .map(|wave| wave.iter().map(|step| step.id.as_str()).collect())
+//!     .collect();
+//! assert_eq!(waves, [vec!["schema"], vec!["api", "ui"]]);
+//!
+//! let path = plan.critical_path(StepWeight::Complexity).unwrap();
+//! assert_eq!(path.step_ids(), ["schema", "api"]);
+//! ```
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use thiserror::Error;
+
+use crate::events::Event;
+use crate::ids::{AgentId, TaskId};
+use crate::models::{PlanDiff, PlanStep, PlanStepChange, StepComplexity, TaskPlan};
+
+/// Why a plan's step graph cannot be scheduled
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PlanError {
+    /// Two steps share an id
+    #[error("Duplicate step id {0:?}")]
+    DuplicateStep(String),
+
+    /// A dependency or agent assignment names a step that does not exist
+    #[error("Unknown step id {0:?}")]
+    UnknownStep(String),
+
+    /// Steps that depend on each other in a loop; the first id is repeated
+    /// at the end
+    #[error("Dependency cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+/// What a step costs when finding the critical path
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StepWeight {
+    /// `StepComplexity::weight`
+    #[default]
+    Complexity,
+    /// `PlanStep::estimated_tokens`, with steps lacking an estimate weighing 0
+    Tokens,
+}
+
+impl StepComplexity {
+    /// Relative effort: 1 for simple, 2 for moderate, 3 for complex
+    pub fn weight(self) -> u64 {
+        match self {
+            StepComplexity::Simple => 1,
+            StepComplexity::Moderate => 2,
+            StepComplexity::Complex => 3,
+        }
+    }
+}
+
+/// The heaviest chain of dependent steps
+#[derive(Debug, Clone)]
+pub struct CriticalPath<'a> {
+    /// Steps in execution order
+    pub steps: Vec<&'a PlanStep>,
+    /// Sum of the steps' weights, saturating at `u64::MAX`
+    pub weight: u64,
+}
+
+impl CriticalPath<'_> {
+    pub fn step_ids(&self) -> Vec<&str> {
+        self.steps.iter().map(|step| step.id.as_str()).collect()
+    }
+}
+
+/// Validated step graph, by index into `steps`
+struct Graph {
+    order: Vec<usize>,
+    preds: Vec<Vec<usize>>,
+}
+
+impl TaskPlan {
+    /// Check for duplicate step ids, unknown ids and dependency cycles
+    pub fn validate(&self) -> Result<(), PlanError> {
+        self.graph().map(|_| ())
+    }
+
+    /// Step with the given id
+    pub fn step(&self, id: &str) -> Option<&PlanStep> {
+        self.steps.iter().find(|step| step.id == id)
+    }
+
+    /// Steps in an order that respects every dependency
+    pub fn topo_order(&self) -> Result<Vec<&PlanStep>, PlanError> {
+        let graph = self.graph()?;
+        Ok(graph.order.iter().map(|&i| &self.steps[i]).collect())
+    }
+
+    /// Steps grouped into waves that can run in parallel
+    ///
+    /// A step is in wave `n` when its longest chain of dependencies has `n`
+    /// steps, so every wave only depends on earlier ones.
+    pub fn waves(&self) -> Result<Vec<Vec<&PlanStep>>, PlanError> {
+        let graph = self.graph()?;
+        let mut depth = vec![0; self.steps.len()];
+        for &i in &graph.order {
+            depth[i] = graph.preds[i].iter().map(|&p| depth[p] + 1).max().unwrap_or(0);
+        }
+        let count = depth.iter().max().map_or(0, |max| max + 1);
+        let mut waves = vec![Vec::new(); count];
+        for (i, step) in self.steps.iter().enumerate() {
+            waves[depth[i]].push(step);
+        }
+        Ok(waves)
+    }
+
+    /// The chain of dependent steps with the largest total weight
+    ///
+    /// An empty plan has an empty path of weight 0.
+    pub fn critical_path(&self, weight: StepWeight) -> Result<CriticalPath<'_>, PlanError> {
+        let graph = self.graph()?;
+        let step_weight = |step: &PlanStep| match weight {
+            StepWeight::Complexity => step.complexity.weight(),
+            StepWeight::Tokens => step.estimated_tokens.unwrap_or(0),
+        };
+
+        let mut total = vec![0u64; self.steps.len()];
+        let mut previous: Vec<Option<usize>> = vec![None; self.steps.len()];
+        for &i in &graph.order {
+            // Heaviest predecessor, earliest in `steps` on ties
+            let best =
+                graph.preds[i].iter().copied().min_by_key(|&p| (std::cmp::Reverse(total[p]), p));
+            previous[i] = best;
+            total[i] = best.map_or(0, |p| total[p]).saturating_add(step_weight(&self.steps[i]));
+        }
+
+        let Some(mut last) =
+            (0..self.steps.len()).min_by_key(|&i| (std::cmp::Reverse(total[i]), i))
+        else {
+            return Ok(CriticalPath { steps: Vec::new(), weight: 0 });
+        };
+        let weight = total[last];
+        let mut steps = vec![&self.steps[last]];
+        while let Some(p) = previous[last] {
+            steps.push(&self.steps[p]);
+            last = p;
+        }
+        steps.reverse();
+        Ok(CriticalPath { steps, weight })
+    }
+
+    /// Unfinished steps whose dependencies have all finished
+    ///
+    /// Works on unvalidated plans; a dependency on an unknown step is
+    /// never satisfied unless that id is in `finished`.
+    pub fn ready_steps(&self, finished: &HashSet<&str>) -> Vec<&PlanStep> {
+        self.steps
+            .iter()
+            .filter(|step| !finished.contains(step.id.as_str()))
+            .filter(|step| {
+                self.dependencies
+                    .iter()
+                    .filter(|(_, to)| *to == step.id)
+                    .all(|(from, _)| finished.contains(from.as_str()))
+            })
+            .collect()
+    }
+
+    fn graph(&self) -> Result<Graph, PlanError> {
+        let mut index = HashMap::new();
+        for (i, step) in self.steps.iter().enumerate() {
+            if index.insert(step.id.as_str(), i).is_some() {
+                return Err(PlanError::DuplicateStep(step.id.clone()));
+            }
+        }
+        let lookup = |id: &String| {
+            index.get(id.as_str()).copied().ok_or_else(|| PlanError::UnknownStep(id.clone()))
+        };
+
+        let mut preds = vec![Vec::new(); self.steps.len()];
+        let mut succs = vec![Vec::new(); self.steps.len()];
+        for (from, to) in &self.dependencies {
+            let (from, to) = (lookup(from)?, lookup(to)?);
+            if !succs[from].contains(&to) {
+                succs[from].push(to);
+                preds[to].push(from);
+            }
+        }
+        let mut assigned: Vec<&String> = self.agent_assignments.keys().collect();
+        assigned.sort();
+        for id in assigned {
+            lookup(id)?;
+        }
+
+        // Kahn's algorithm, always taking the earliest ready step
+        let mut waiting: Vec<usize> = preds.iter().map(Vec::len).collect();
+        let mut ready: BTreeSet<usize> =
+            (0..self.steps.len()).filter(|&i| waiting[i] == 0).collect();
+        let mut order = Vec::with_capacity(self.steps.len());
+        while let Some(i) = ready.pop_first() {
+            order.push(i);
+            for &next in &succs[i] {
+                waiting[next] -= 1;
+                if waiting[next] == 0 {
+                    ready.insert(next);
+                }
+            }
+        }
+        if order.len() < self.steps.len() {
+            return Err(PlanError::Cycle(self.find_cycle(&waiting, &preds)));
+        }
+        Ok(Graph { order, preds })
+    }
+
+    /// A cycle among the steps Kahn's algorithm could not order
+    ///
+    /// Every such step has an unordered predecessor, so walking backwards
+    /// from one must revisit a step.
+    fn find_cycle(&self, waiting: &[usize], preds: &[Vec<usize>]) -> Vec<String> {
+        let stuck = |i: usize| waiting[i] > 0;
+        let start = (0..self.steps.len()).find(|&i| stuck(i)).expect("an unordered step");
+        let mut path = vec![start];
+        let mut current = start;
+        loop {
+            let pred = preds[current]
+                .iter()
+                .copied()
+                .find(|&p| stuck(p))
+                .expect("an unordered predecessor");
+            if let Some(pos) = path.iter().position(|&i| i == pred) {
+                let mut cycle: Vec<usize> = path[pos..].iter().rev().copied().collect();
+                let first =
+                    cycle.iter().enumerate().min_by_key(|(_, &i)| i).map_or(0, |(at, _)| at);
+                cycle.rotate_left(first);
+                cycle.push(cycle[0]);
+                return cycle.into_iter().map(|i| self.steps[i].id.clone()).collect();
+            }
+            path.push(pred);
+            current = pred;
+        }
+    }
+}
+
+impl PlanDiff {
+    /// Step changes that turn `old` into `new`
+    pub fn between(old: &TaskPlan, new: &TaskPlan) -> Self {
+        let before: HashMap<&str, &PlanStep> =
+            old.steps.iter().map(|step| (step.id.as_str(), step)).collect();
+        let after: HashSet<&str> = new.steps.iter().map(|step| step.id.as_str()).collect();
+        let mut diff = Self::default();
+        for step in &new.steps {
+            match before.get(step.id.as_str()) {
+                None => diff.added.push(step.clone()),
+                Some(&previous) if previous != step => diff
+                    .changed
+                    .push(PlanStepChange { before: previous.clone(), after: step.clone() }),
+                Some(_) => {}
+            }
+        }
+        diff.removed =
+            old.steps.iter().filter(|step| !after.contains(step.id.as_str())).cloned().collect();
+        diff
+    }
+
+    /// Check whether no step was added, removed or changed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Where a plan step is in its execution
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum StepStatus {
+    #[default]
+    Pending,
+    Running,
+    Completed,
+    Failed {
+        error: String,
+    },
+    Skipped {
+        reason: Option<String>,
+    },
+}
+
+impl StepStatus {
+    /// Check whether the step has completed, failed or been skipped
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            StepStatus::Completed | StepStatus::Failed { .. } | StepStatus::Skipped { .. }
+        )
+    }
+}
+
+/// Progress of one plan step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepProgress {
+    pub step_id: String,
+    pub status: StepStatus,
+    /// Agent named by the latest event about the step
+    pub agent_id: Option<AgentId>,
+    /// Task named by the latest event about the step
+    pub task_id: Option<TaskId>,
+}
+
+/// Per-step progress of the current plan, folded from plan events
+///
+/// `PlanCreated` starts over with every step pending. `PlanRevised` keeps the
+/// progress of steps it leaves untouched, but steps in its `diff.changed` go
+/// back to pending since their new content has not run. Step events apply in
+/// arrival order, so a failed step that is started again is running again;
+/// events for step ids not in the plan are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct PlanProgress {
+    /// One entry per plan step, in plan order
+    pub steps: Vec<StepProgress>,
+}
+
+impl PlanProgress {
+    /// Every step of `plan` pending
+    pub fn new(plan: &TaskPlan) -> Self {
+        let steps = plan
+            .steps
+            .iter()
+            .map(|step| StepProgress {
+                step_id: step.id.clone(),
+                status: StepStatus::Pending,
+                agent_id: None,
+                task_id: None,
+            })
+            .collect();
+        Self { steps }
+    }
+
+    /// Build progress by applying every event in order
+    pub fn from_events<'a>(events: impl IntoIterator<Item = &'a Event>) -> Self {
+        let mut progress = Self::default();
+        for event in events {
+            progress.apply(event);
+        }
+        progress
+    }
+
+    /// Fold one event in
+    pub fn apply(&mut self, event: &Event) {
+        let (step_id, status, agent_id, task_id) = match event {
+            Event::PlanCreated { plan, .. } => {
+                *self = Self::new(plan);
+                return;
+            }
+            Event::PlanRevised { plan, diff, .. } => {
+                let mut revised = Self::new(plan);
+                let changed = |id: &str| diff.changed.iter().any(|change| change.after.id == id);
+                for step in revised.steps.iter_mut().filter(|step| !changed(&step.step_id)) {
+                    if let Some(previous) = self.step(&step.step_id) {
+                        *step = previous.clone();
+                    }
+                }
+                *self = revised;
+                return;
+            }
+            Event::PlanStepStarted { step_id, agent_id, task_id, .. } => {
+                (step_id, StepStatus::Running, Some(*agent_id), Some(*task_id))
+            }
+            Event::PlanStepCompleted { step_id, agent_id, task_id, .. } => {
+                (step_id, StepStatus::Completed, Some(*agent_id), Some(*task_id))
+            }
+            Event::PlanStepFailed { step_id, agent_id, task_id, error, .. } => (
+                step_id,
+                StepStatus::Failed { error: error.clone() },
+                Some(*agent_id),
+                Some(*task_id),
+            ),
+            Event::PlanStepSkipped { step_id, agent_id, task_id, reason, .. } => {
+                (step_id, StepStatus::Skipped { reason: reason.clone() }, *agent_id, *task_id)
+            }
+            _ => return,
+        };
+        if let Some(step) = self.steps.iter_mut().find(|step| step.step_id == *step_id) {
+            step.status = status;
+            step.agent_id = agent_id.or(step.agent_id);
+            step.task_id = task_id.or(step.task_id);
+        }
+    }
+
+    /// Look up a step
+    pub fn step(&self, step_id: &str) -> Option<&StepProgress> {
+        self.steps.iter().find(|step| step.step_id == step_id)
+    }
+
+    /// Fraction of steps completed or skipped, from 0.0 to 1.0
+    ///
+    /// Failed steps do not count. A plan with no steps is at 0.0.
+    pub fn completion(&self) -> f64 {
+        if self.steps.is_empty() {
+            return 0.0;
+        }
+        let done = self
+            .steps
+            .iter()
+            .filter(|step| {
+                matches!(step.status, StepStatus::Completed | StepStatus::Skipped { .. })
+            })
+            .count();
+        done as f64 / self.steps.len() as f64
+    }
+
+    /// Check whether there is a plan and every step has finished
+    pub fn is_finished(&self) -> bool {
+        !self.steps.is_empty() && self.steps.iter().all(|step| step.status.is_finished())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::sample_plan;
+    use crate::ids::SubmissionId;
+    use crate::models::AgentRole;
+    use StepComplexity::{Complex, Moderate, Simple};
+
+    fn plan(
+        steps: &[(&str, StepComplexity, Option<u64>)],
+        dependencies: &[(&str, &str)],
+    ) -> TaskPlan {
+        TaskPlan {
+            original_request: "test".into(),
+            steps: steps
+                .iter()
+                .map(|(id, complexity, tokens)| PlanStep {
+                    id: id.to_string(),
+                    description: id.to_string(),
+                    expected_outcome: String::new(),
+                    complexity: *complexity,
+                    estimated_tokens: *tokens,
+                })
+                .collect(),
+            agent_assignments: Default::default(),
+            dependencies: dependencies
+                .iter()
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+                .collect(),
+            estimated_tokens: 0,
+        }
+    }
+
+    fn ids<'a>(steps: &[&'a PlanStep]) -> Vec<&'a str> {
+        steps.iter().map(|step| step.id.as_str()).collect()
+    }
+
+    /// a -> b -> d, a -> c -> d, e independent
+    fn diamond() -> TaskPlan {
+        plan(
+            &[
+                ("a", Simple, Some(100)),
+                ("b", Complex, Some(500)),
+                ("c", Moderate, Some(2_000)),
+                ("d", Simple, Some(10)),
+                ("e", Complex, None),
+            ],
+            &[("a", "b"), ("a", "c"), ("b", "d"), ("c", "d"), ("a", "b")],
+        )
+    }
+
+    // === Validation Tests ===
+
+    #[test]
+    fn test_validate_accepts_dag() {
+        assert_eq!(sample_plan().validate(), Ok(()));
+        assert_eq!(diamond().validate(), Ok(()));
+        assert_eq!(plan(&[], &[]).validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_ids() {
+        let duplicate = plan(&[("a", Simple, None), ("a", Simple, None)], &[]);
+        assert_eq!(duplicate.validate(), Err(PlanError::DuplicateStep("a".into())));
+
+        let unknown = plan(&[("a", Simple, None)], &[("a", "z")]);
+        assert_eq!(unknown.validate(), Err(PlanError::UnknownStep("z".into())));
+
+        let mut assigned = plan(&[("a", Simple, None)], &[]);
+        assigned.agent_assignments.insert("ghost".into(), AgentRole::Worker);
+        assert_eq!(assigned.validate(), Err(PlanError::UnknownStep("ghost".into())));
+    }
+
+    #[test]
+    fn test_validate_reports_cycle() {
+        let cyclic = plan(
+            &[("x", Simple, None), ("a", Simple, None), ("b", Simple, None), ("c", Simple, None)],
+            &[("x", "a"), ("a", "b"), ("b", "c"), ("c", "a")],
+        );
+        let err = cyclic.validate().unwrap_err();
+        assert_eq!(err, PlanError::Cycle(vec!["a".into(), "b".into(), "c".into(), "a".into()]));
+        assert_eq!(err.to_string(), "Dependency cycle: a -> b -> c -> a");
+
+        let self_loop = plan(&[("a", Simple, None)], &[("a", "a")]);
+        assert_eq!(
+            self_loop.topo_order().unwrap_err(),
+            PlanError::Cycle(vec!["a".into(), "a".into()])
+        );
+    }
+
+    // === Ordering Tests ===
+
+    #[test]
+    fn test_topo_order_and_waves() {
+        let diamond = diamond();
+        assert_eq!(ids(&diamond.topo_order().unwrap()), ["a", "b", "c", "d", "e"]);
+
+        let waves: Vec<Vec<&str>> = diamond.waves().unwrap().iter().map(|wave| ids(wave)).collect();
+        assert_eq!(waves, [vec!["a", "e"], vec!["b", "c"], vec!["d"]]);
+        let empty = plan(&[], &[]);
+        assert!(empty.waves().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_critical_path_by_weight() {
+        let diamond = diamond();
+        let by_complexity = diamond.critical_path(StepWeight::Complexity).unwrap();
+        assert_eq!(by_complexity.step_ids(), ["a", "b", "d"]);
+        assert_eq!(by_complexity.weight, 5);
+
+        let by_tokens = diamond.critical_path(StepWeight::Tokens).unwrap();
+        assert_eq!(by_tokens.step_ids(), ["a", "c", "d"]);
+        assert_eq!(by_tokens.weight, 2_110);
+
+        let empty = plan(&[], &[]);
+        assert!(empty.critical_path(StepWeight::Tokens).unwrap().steps.is_empty());
+    }
+
+    #[test]
+    fn test_critical_path_saturates() {
+        let huge = plan(&[("b", Simple, Some(1)), ("a", Simple, Some(u64::MAX))], &[("a", "b")]);
+        let path = huge.critical_path(StepWeight::Tokens).unwrap();
+        assert_eq!(path.step_ids(), ["a", "b"]);
+        assert_eq!(path.weight, u64::MAX);
+    }
+
+    #[test]
+    fn test_ready_steps() {
+        let plan = diamond();
+        assert_eq!(ids(&plan.ready_steps(&HashSet::new())), ["a", "e"]);
+        assert_eq!(ids(&plan.ready_steps(&["a", "b"].into())), ["c", "e"]);
+        assert_eq!(ids(&plan.ready_steps(&["a", "b", "c", "e"].into())), ["d"]);
+    }
+
+    // === Progress Tests ===
+
+    fn step_event(step_id: &str, status: StepStatus, agent_id: AgentId, task_id: TaskId) -> Event {
+        let (sub_id, step_id) = (SubmissionId::new(), step_id.to_string());
+        match status {
+            StepStatus::Running => Event::PlanStepStarted { sub_id, step_id, agent_id, task_id },
+            StepStatus::Completed => {
+                Event::PlanStepCompleted { sub_id, step_id, agent_id, task_id }
+            }
+            StepStatus::Failed { error } => {
+                Event::PlanStepFailed { sub_id, step_id, agent_id, task_id, error }
+            }
+            StepStatus::Skipped { reason } => {
+                Event::PlanStepSkipped { sub_id, step_id, agent_id: None, task_id: None, reason }
+            }
+            StepStatus::Pending => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_progress_tracks_steps() {
+        let (agent, task) = (AgentId::new(), TaskId::new());
+        let mut progress = PlanProgress::default();
+        assert_eq!(progress.completion(), 0.0);
+
+        progress.apply(&Event::PlanCreated { sub_id: SubmissionId::new(), plan: diamond() });
+        assert_eq!(progress.steps.len(), 5);
+        assert!(progress.steps.iter().all(|step| step.status == StepStatus::Pending));
+
+        progress.apply(&step_event("a", StepStatus::Running, agent, task));
+        progress.apply(&step_event("a", StepStatus::Completed, agent, task));
+        progress.apply(&step_event("e", StepStatus::Skipped { reason: None }, agent, task));
+        progress.apply(&step_event("b", StepStatus::Failed { error: "boom".into() }, agent, task));
+        progress.apply(&step_event("ghost", StepStatus::Completed, agent, task));
+
+        let a = progress.step("a").unwrap();
+        assert_eq!(
+            (a.status.clone(), a.agent_id, a.task_id),
+            (StepStatus::Completed, Some(agent), Some(task))
+        );
+        assert_eq!(progress.step("e").unwrap().agent_id, None);
+        assert!(progress.step("ghost").is_none());
+        assert_eq!(progress.completion(), 0.4);
+        assert!(!progress.is_finished());
+    }
+
+    #[test]
+    fn test_progress_retry_and_reset() {
+        let (agent, task) = (AgentId::new(), TaskId::new());
+        let created = Event::PlanCreated {
+            sub_id: SubmissionId::new(),
+            plan: plan(&[("a", Simple, None)], &[]),
+        };
+        let events = [
+            created.clone(),
+            step_event("a", StepStatus::Failed { error: "flaky".into() }, agent, task),
+            step_event("a", StepStatus::Running, agent, TaskId::new()),
+            step_event("a", StepStatus::Completed, agent, task),
+        ];
+        let mut progress = PlanProgress::from_events(&events);
+        assert_eq!(progress.completion(), 1.0);
+        assert!(progress.is_finished());
+
+        progress.apply(&created);
+        assert_eq!(progress.step("a").unwrap().status, StepStatus::Pending);
+    }
+
+    // === Revision Tests ===
+
+    #[test]
+    fn test_diff_between_plans() {
+        let old = diamond();
+        let mut new = diamond();
+        new.steps.retain(|step| step.id != "e");
+        new.steps[1].description = "b, but smaller".into();
+        new.steps.insert(0, plan(&[("f", Simple, None)], &[]).steps.remove(0));
+
+        let diff = PlanDiff::between(&old, &new);
+        assert_eq!(ids(&diff.added.iter().collect::<Vec<_>>()), ["f"]);
+        assert_eq!(ids(&diff.removed.iter().collect::<Vec<_>>()), ["e"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].before.description, "b");
+        assert_eq!(diff.changed[0].after.description, "b, but smaller");
+
+        assert!(PlanDiff::between(&old, &diamond()).is_empty());
+    }
+
+    #[test]
+    fn test_revision_resets_changed_steps() {
+        let (agent, task) = (AgentId::new(), TaskId::new());
+        let old = diamond();
+        let mut new = plan(
+            &[("a", Simple, None), ("b", Complex, Some(500)), ("z", Simple, None)],
+            &[("a", "z")],
+        );
+        new.steps[0].description = "a, reworded".into();
+
+        let mut progress = PlanProgress::default();
+        progress.apply(&Event::PlanCreated { sub_id: SubmissionId::new(), plan: old.clone() });
+        progress.apply(&step_event("a", StepStatus::Completed, agent, task));
+        progress.apply(&step_event("b", StepStatus::Completed, agent, task));
+        progress.apply(&Event::PlanRevised {
+            sub_id: SubmissionId::new(),
+            diff: PlanDiff::between(&old, &new),
+            plan: new,
+        });
+
+        let steps: Vec<(&str, &StepStatus)> =
+            progress.steps.iter().map(|step| (step.step_id.as_str(), &step.status)).collect();
+        assert_eq!(
+            steps,
+            [
+                ("a", &StepStatus::Pending),
+                ("b", &StepStatus::Completed),
+                ("z", &StepStatus::Pending)
+            ]
+        );
+        assert_eq!(progress.step("b").unwrap().agent_id, Some(agent));
+    }
+}
@@ -0,0 +1,314 @@
+//! Evaluating `ApprovalMode::Custom` policies
+//!
+//! An [`ApprovalPolicy`] is an ordered list of rules carried in
+//! `SessionConfig::approval_policy`. Each `ApprovalRequired` event becomes an
+//! [`ApprovalRequest`]; the first rule whose conditions all hold decides
+//! whether the call is allowed, denied or put to the user, and the
+//! [`ApprovalDecision`] names that rule for the audit trail.
+//!
+//! ```
+//! use warhorn::policy::ApprovalRequest;
+//! use warhorn::{ApprovalAction, ApprovalPolicy, ApprovalRule, RiskLevel};
+//!
+//! let policy = ApprovalPolicy {
+//!     rules: vec![ApprovalRule {
+//!         name: Some("read-only tools".into()),
+//!         tools: vec!["read_*".into()],
+//!         max_risk: Some(RiskLevel::Low),
+//!         action: ApprovalAction::Allow,
+//!         ..Default::default()
+//!     }],
+//!     default_action: ApprovalAction::Ask,
+//! };
+//!
+//! let arguments = serde_json::json!({ "path": "src/lib.rs" });
+//! let request = ApprovalRequest::new("read_file", &arguments, RiskLevel::None);
+//! let decision = policy.evaluate(&request);
+//! assert_eq!(decision.action, ApprovalAction::Allow);
+//! assert_eq!(decision.rule_index, Some(0));
+//! ```
+
+use std::path::Path;
+
+use crate::events::Event;
+use crate::glob::{glob_match, wildcard_match};
+use crate::models::{
+    AgentRole, ApprovalAction, ApprovalPolicy, ApprovalRule, ArgumentCondition, RiskLevel,
+};
+
+/// The facts about a tool call that approval rules can match on
+#[derive(Debug, Clone, Copy)]
+pub struct ApprovalRequest<'a> {
+    pub tool_name: &'a str,
+    pub arguments: &'a serde_json::Value,
+    pub risk: RiskLevel,
+    /// Role of the requesting agent, if known
+    pub role: Option<&'a AgentRole>,
+    /// Session working directory, if known
+    pub cwd: Option<&'a Path>,
+}
+
+impl<'a> ApprovalRequest<'a> {
+    pub fn new(tool_name: &'a str, arguments: &'a serde_json::Value, risk: RiskLevel) -> Self {
+        Self { tool_name, arguments, risk, role: None, cwd: None }
+    }
+
+    /// Build a request from an `ApprovalRequired` event
+    ///
+    /// The event does not carry the agent's role or the session cwd; add
+    /// them with [`with_role`](Self::with_role) and [`with_cwd`](Self::with_cwd).
+    pub fn from_event(event: &'a Event) -> Option<Self> {
+        match event {
+            Event::ApprovalRequired { tool_name, arguments, risk, .. } => {
+                Some(Self::new(tool_name, arguments, *risk))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn with_role(mut self, role: &'a AgentRole) -> Self {
+        self.role = Some(role);
+        self
+    }
+
+    pub fn with_cwd(mut self, cwd: &'a Path) -> Self {
+        self.cwd = Some(cwd);
+        self
+    }
+}
+
+/// What a policy decided, and which rule decided it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApprovalDecision<'a> {
+    pub action: ApprovalAction,
+    /// Position of the deciding rule; `None` when the default applied
+    pub rule_index: Option<usize>,
+    pub rule: Option<&'a ApprovalRule>,
+}
+
+impl ApprovalPolicy {
+    /// Decide a request using the first matching rule, or the default action
+    pub fn evaluate(&self, request: &ApprovalRequest<'_>) -> ApprovalDecision<'_> {
+        match self.rules.iter().enumerate().find(|(_, rule)| rule.matches(request)) {
+            Some((index, rule)) => ApprovalDecision {
+                action: rule.action,
+                rule_index: Some(index),
+                rule: Some(rule),
+            },
+            None => ApprovalDecision { action: self.default_action, rule_index: None, rule: None },
+        }
+    }
+}
+
+impl ApprovalRule {
+    /// Check whether every condition set on this rule holds for `request`
+    ///
+    /// A rule that restricts roles or cwd never matches a request that
+    /// lacks that information.
+    pub fn matches(&self, request: &ApprovalRequest<'_>) -> bool {
+        let tool_ok = self.tools.is_empty()
+            || self.tools.iter().any(|pattern| wildcard_match(pattern, request.tool_name));
+        let risk_ok = self.min_risk.is_none_or(|min| request.risk >= min)
+            && self.max_risk.is_none_or(|max| request.risk <= max);
+        let role_ok = self.roles.is_empty()
+            || request.role.is_some_and(|role| self.roles.contains(role));
+        let cwd_ok = match (&self.cwd, request.cwd) {
+            (None, _) => true,
+            (Some(pattern), Some(cwd)) => glob_match(pattern, &cwd.to_string_lossy()),
+            (Some(_), None) => false,
+        };
+        tool_ok
+            && risk_ok
+            && role_ok
+            && cwd_ok
+            && self.arguments.iter().all(|condition| condition.matches(request.arguments))
+    }
+}
+
+impl ArgumentCondition {
+    /// Check this condition against a tool call's arguments
+    pub fn matches(&self, arguments: &serde_json::Value) -> bool {
+        match self {
+            ArgumentCondition::Exists { pointer } => arguments.pointer(pointer).is_some(),
+            ArgumentCondition::Absent { pointer } => arguments.pointer(pointer).is_none(),
+            ArgumentCondition::Equals { pointer, value } => {
+                arguments.pointer(pointer) == Some(value)
+            }
+            ArgumentCondition::Matches { pointer, pattern } => arguments
+                .pointer(pointer)
+                .and_then(serde_json::Value::as_str)
+                .is_some_and(|text| wildcard_match(pattern, text)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ids::{AgentId, CallId, SubmissionId};
+    use crate::models::SessionConfig;
+    use serde_json::json;
+
+    fn rule(action: ApprovalAction) -> ApprovalRule {
+        ApprovalRule { action, ..Default::default() }
+    }
+
+    fn policy() -> ApprovalPolicy {
+        ApprovalPolicy {
+            rules: vec![
+                ApprovalRule {
+                    name: Some("no force push".into()),
+                    tools: vec!["shell".into()],
+                    arguments: vec![ArgumentCondition::Matches {
+                        pointer: "/command".into(),
+                        pattern: "git push --force*".into(),
+                    }],
+                    ..rule(ApprovalAction::Deny)
+                },
+                ApprovalRule {
+                    name: Some("scouts read freely".into()),
+                    roles: vec![AgentRole::Scout],
+                    max_risk: Some(RiskLevel::Low),
+                    ..rule(ApprovalAction::Allow)
+                },
+                ApprovalRule {
+                    name: Some("edits inside the workspace".into()),
+                    tools: vec!["edit_*".into(), "write_*".into()],
+                    cwd: Some("/work/**".into()),
+                    max_risk: Some(RiskLevel::Medium),
+                    ..rule(ApprovalAction::Allow)
+                },
+                ApprovalRule {
+                    name: Some("critical".into()),
+                    min_risk: Some(RiskLevel::Critical),
+                    ..rule(ApprovalAction::Deny)
+                },
+            ],
+            default_action: ApprovalAction::Ask,
+        }
+    }
+
+    // === Rule Matching Tests ===
+
+    #[test]
+    fn test_argument_conditions() {
+        let arguments = json!({ "command": "rm -rf target", "options": { "dry_run": true } });
+        let exists = ArgumentCondition::Exists { pointer: "/options/dry_run".into() };
+        let absent = ArgumentCondition::Absent { pointer: "/cwd".into() };
+        let equals =
+            ArgumentCondition::Equals { pointer: "/options/dry_run".into(), value: json!(true) };
+        let glob =
+            ArgumentCondition::Matches { pointer: "/command".into(), pattern: "rm *".into() };
+        let not_string =
+            ArgumentCondition::Matches { pointer: "/options".into(), pattern: "*".into() };
+
+        assert!(exists.matches(&arguments));
+        assert!(absent.matches(&arguments));
+        assert!(equals.matches(&arguments));
+        assert!(glob.matches(&arguments));
+        assert!(!not_string.matches(&arguments));
+
+        let root = json!({ "command": "rm -rf /" });
+        assert!(glob.matches(&root));
+        let slashed_tool =
+            ApprovalRule { tools: vec!["mcp/*".into()], ..rule(ApprovalAction::Deny) };
+        let push = ApprovalRequest::new("mcp/github/push", &root, RiskLevel::Low);
+        assert!(slashed_tool.matches(&push));
+    }
+
+    #[test]
+    fn test_rule_without_context_does_not_match_role_or_cwd() {
+        let arguments = json!({});
+        let request = ApprovalRequest::new("edit_file", &arguments, RiskLevel::Low);
+        let scout = ApprovalRule { roles: vec![AgentRole::Scout], ..rule(ApprovalAction::Allow) };
+        let in_work = ApprovalRule { cwd: Some("/work/**".into()), ..rule(ApprovalAction::Allow) };
+        assert!(!scout.matches(&request));
+        assert!(!in_work.matches(&request));
+        assert!(rule(ApprovalAction::Allow).matches(&request));
+    }
+
+    // === Policy Evaluation Tests ===
+
+    #[test]
+    fn test_first_matching_rule_decides() {
+        let policy = policy();
+        let arguments = json!({ "command": "git push --force origin feature/x" });
+        let scout = AgentRole::Scout;
+        let request = ApprovalRequest::new("shell", &arguments, RiskLevel::Low).with_role(&scout);
+
+        let decision = policy.evaluate(&request);
+        assert_eq!(decision.action, ApprovalAction::Deny);
+        assert_eq!(decision.rule_index, Some(0));
+        assert_eq!(decision.rule.unwrap().name.as_deref(), Some("no force push"));
+    }
+
+    #[test]
+    fn test_risk_cwd_and_default() {
+        let policy = policy();
+        let arguments = json!({ "path": "src/main.rs" });
+        let cwd = Path::new("/work/app");
+
+        let edit = ApprovalRequest::new("edit_file", &arguments, RiskLevel::Medium).with_cwd(cwd);
+        assert_eq!(policy.evaluate(&edit).rule_index, Some(2));
+
+        let risky_edit = ApprovalRequest { risk: RiskLevel::High, ..edit };
+        let decision = policy.evaluate(&risky_edit);
+        assert_eq!(decision.action, ApprovalAction::Ask);
+        assert_eq!(decision.rule, None);
+
+        let critical = ApprovalRequest { risk: RiskLevel::Critical, ..edit };
+        assert_eq!(policy.evaluate(&critical).action, ApprovalAction::Deny);
+
+        let outside = edit.with_cwd(Path::new("/etc"));
+        assert_eq!(policy.evaluate(&outside).action, ApprovalAction::Ask);
+    }
+
+    #[test]
+    fn test_request_from_event() {
+        let event = Event::ApprovalRequired {
+            sub_id: SubmissionId::new(),
+            agent_id: AgentId::new(),
+            call_id: CallId::new(),
+            tool_name: "shell".into(),
+            arguments: json!({ "command": "git push --force" }),
+            description: "Force push".into(),
+            risk: RiskLevel::High,
+        };
+        let request = ApprovalRequest::from_event(&event).unwrap();
+        assert_eq!(request.tool_name, "shell");
+        assert_eq!(request.risk, RiskLevel::High);
+        assert_eq!(policy().evaluate(&request).action, ApprovalAction::Deny);
+
+        let other =
+            Event::Warning { sub_id: SubmissionId::new(), message: "hm".into(), details: None };
+        assert!(ApprovalRequest::from_event(&other).is_none());
+    }
+
+    // === Serialization Tests ===
+
+    #[test]
+    fn test_policy_in_session_config() {
+        let json = r#"{
+            "approval_mode": "custom",
+            "approval_policy": {
+                "rules": [{
+                    "name": "no force push",
+                    "tools": ["shell"],
+                    "arguments": [{
+                        "type": "matches",
+                        "pointer": "/command",
+                        "pattern": "git push --force*"
+                    }],
+                    "action": "deny"
+                }],
+                "default_action": "ask"
+            }
+        }"#;
+        let config: SessionConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.approval_policy.as_ref().unwrap().rules, policy().rules[..1]);
+
+        let roundtrip: SessionConfig =
+            serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+        assert_eq!(roundtrip.approval_policy, config.approval_policy);
+    }
+}
@@ -0,0 +1,596 @@
+//! Token pricing and cost breakdowns
+//!
+//! A [`PricingTable`] maps model names to per-million-token rates for input,
+//! output, cache reads and cache writes. Keys may end in `*` to cover a model
+//! family; an exact name wins over a pattern, and a longer pattern over a
+//! shorter one. Rates and costs are [`Usd`], a fixed-point decimal, so
+//! summing thousands of updates does not drift the way `f64` does.
+//!
+//! ```
+//! use warhorn::pricing::{PricingTable, Usd};
+//! use warhorn::TokenUsage;
+//!
+//! let table = PricingTable::from_json(r#"{
+//!     "model-large*": {
+//!         "input": "3",
+//!         "output": "15",
+//!         "cache_read": "0.30",
+//!         "cache_write": "3.75"
+//!     }
+//! }"#).unwrap();
+//!
+//! let usage = TokenUsage { input_tokens: 1_000, output_tokens: 200, ..Default::default() };
+//! assert_eq!(table.cost("model-large-2025", &usage), Some("0.006".parse::<Usd>().unwrap()));
+//! ```
+//!
+//! [`CostLedger`] folds `UsageUpdate` events into totals per agent, role,
+//! model and task.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub};
+use std::str::FromStr;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+use crate::error::ProtocolError;
+use crate::events::Event;
+use crate::glob::wildcard_match;
+use crate::ids::{AgentId, SubmissionId, TaskId};
+use crate::models::{AgentRole, TokenUsage};
+
+/// Decimal places kept by [`Usd`]
+const SCALE: u32 = 12;
+const UNIT: i128 = 10_i128.pow(SCALE);
+const TOKENS_PER_RATE: i128 = 1_000_000;
+
+/// An amount of US dollars, fixed-point with 12 decimal places
+///
+/// Parses from and serializes to a decimal string such as `"0.0125"`;
+/// also deserializes from JSON or TOML numbers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Usd(i128);
+
+/// A string that is not a decimal dollar amount
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid USD amount {0:?}")]
+pub struct ParseUsdError(String);
+
+impl Usd {
+    pub const ZERO: Usd = Usd(0);
+    pub const MAX: Usd = Usd(i128::MAX);
+    pub const MIN: Usd = Usd(i128::MIN);
+
+    /// Amount in units of 10⁻¹² dollars
+    pub fn from_picos(picos: i128) -> Self {
+        Usd(picos)
+    }
+
+    pub fn picos(self) -> i128 {
+        self.0
+    }
+
+    /// Nearest `f64`, for filling `TokenUsage::estimated_cost_usd`
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / UNIT as f64
+    }
+
+    /// Round half away from zero to `places` decimal places
+    pub fn round(self, places: u32) -> Usd {
+        if places >= SCALE {
+            return self;
+        }
+        let step = 10_i128.pow(SCALE - places);
+        let half = step / 2 * self.0.signum();
+        Usd((self.0 + half) / step * step)
+    }
+}
+
+impl FromStr for Usd {
+    type Err = ParseUsdError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseUsdError(text.to_string());
+        let (negative, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let all_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+        if whole.is_empty()
+            || !all_digits(whole)
+            || !all_digits(fraction)
+            || fraction.len() > SCALE as usize
+        {
+            return Err(invalid());
+        }
+        let whole: i128 = whole.parse().map_err(|_| invalid())?;
+        let fraction = format!("{fraction:0<width$}", width = SCALE as usize);
+        let fraction: i128 = fraction.parse().map_err(|_| invalid())?;
+        let picos =
+            whole.checked_mul(UNIT).and_then(|w| w.checked_add(fraction)).ok_or_else(invalid)?;
+        Ok(Usd(if negative { -picos } else { picos }))
+    }
+}
+
+impl fmt::Display for Usd {
+    /// At least two decimal places, more only when needed
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        let unit = UNIT as u128;
+        let fraction = format!("{:012}", abs % unit);
+        let fraction = fraction.trim_end_matches('0');
+        write!(f, "{sign}{}.{fraction:0<2}", abs / unit)
+    }
+}
+
+impl Add for Usd {
+    type Output = Usd;
+
+    fn add(self, other: Usd) -> Usd {
+        Usd(self.0.saturating_add(other.0))
+    }
+}
+
+impl AddAssign for Usd {
+    fn add_assign(&mut self, other: Usd) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Usd {
+    type Output = Usd;
+
+    fn sub(self, other: Usd) -> Usd {
+        Usd(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Sum for Usd {
+    fn sum<I: Iterator<Item = Usd>>(iter: I) -> Usd {
+        iter.fold(Usd::ZERO, Add::add)
+    }
+}
+
+impl Serialize for Usd {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Usd {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct UsdVisitor;
+
+        impl Visitor<'_> for UsdVisitor {
+            type Value = Usd;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a decimal dollar amount")
+            }
+
+            fn visit_str<E: de::Error>(self, text: &str) -> Result<Usd, E> {
+                text.parse().map_err(E::custom)
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Usd, E> {
+                Ok(Usd(i128::from(value) * UNIT))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Usd, E> {
+                Ok(Usd(i128::from(value) * UNIT))
+            }
+
+            // Rounded to picodollars, so computed rates like 0.30000000000000004 are accepted
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Usd, E> {
+                if !value.is_finite() {
+                    return Err(E::custom(ParseUsdError(value.to_string())));
+                }
+                format!("{value:.prec$}", prec = SCALE as usize).parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(UsdVisitor)
+    }
+}
+
+/// Rates for one model, in dollars per million tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input: Usd,
+    pub output: Usd,
+    #[serde(default)]
+    pub cache_read: Usd,
+    #[serde(default)]
+    pub cache_write: Usd,
+}
+
+impl ModelPricing {
+    /// Cost of `usage`, rounded to the nearest 10⁻¹² dollars
+    ///
+    /// Saturates at [`Usd::MAX`] or [`Usd::MIN`] rather than overflowing.
+    pub fn cost(&self, usage: &TokenUsage) -> Usd {
+        let scaled: i128 = [
+            (usage.input_tokens, self.input),
+            (usage.output_tokens, self.output),
+            (usage.cache_read_tokens, self.cache_read),
+            (usage.cache_write_tokens, self.cache_write),
+        ]
+        .into_iter()
+        .map(|(tokens, rate)| {
+            let limit = if rate.0 < 0 { i128::MIN } else { i128::MAX };
+            i128::from(tokens).checked_mul(rate.0).unwrap_or(limit)
+        })
+        .fold(0, i128::saturating_add);
+        Usd(scaled.saturating_add(TOKENS_PER_RATE / 2) / TOKENS_PER_RATE)
+    }
+}
+
+/// Rates per model name or `*` pattern
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PricingTable {
+    pub models: BTreeMap<String, ModelPricing>,
+}
+
+impl PricingTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a JSON object of model name to rates
+    pub fn from_json(text: &str) -> Result<Self, ProtocolError> {
+        serde_json::from_str(text)
+            .map_err(|e| ProtocolError::DeserializationError { message: e.to_string() })
+    }
+
+    /// Parse a TOML document with one table per model
+    #[cfg(feature = "toml")]
+    pub fn from_toml(text: &str) -> Result<Self, ProtocolError> {
+        toml::from_str(text)
+            .map_err(|e| ProtocolError::DeserializationError { message: e.to_string() })
+    }
+
+    pub fn insert(&mut self, model: impl Into<String>, pricing: ModelPricing) {
+        self.models.insert(model.into(), pricing);
+    }
+
+    /// Rates for `model`: the exact entry, else the longest matching pattern
+    pub fn get(&self, model: &str) -> Option<&ModelPricing> {
+        self.models.get(model).or_else(|| {
+            self.models
+                .iter()
+                .filter(|(pattern, _)| pattern.contains('*') && wildcard_match(pattern, model))
+                .max_by_key(|(pattern, _)| pattern.len())
+                .map(|(_, pricing)| pricing)
+        })
+    }
+
+    /// Cost of `usage` on `model`, or `None` if the model has no rates
+    pub fn cost(&self, model: &str, usage: &TokenUsage) -> Option<Usd> {
+        self.get(model).map(|pricing| pricing.cost(usage))
+    }
+}
+
+/// Usage and cost summed over some set of `UsageUpdate`s
+#[derive(Debug, Clone, Default)]
+pub struct CostTotals {
+    pub usage: TokenUsage,
+    /// Cost of the updates whose model has rates
+    pub cost: Usd,
+    /// Tokens from updates with no known model or no rates for it
+    pub unpriced_tokens: u64,
+}
+
+impl CostTotals {
+    fn add(&mut self, usage: &TokenUsage, cost: Option<Usd>) {
+        self.usage.accumulate(usage);
+        match cost {
+            Some(cost) => self.cost += cost,
+            None => self.unpriced_tokens += usage.total_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AgentInfo {
+    role: AgentRole,
+    model: Option<String>,
+    task_id: Option<TaskId>,
+}
+
+/// Costs of a session broken down by agent, role, model and task
+///
+/// Feed it every event with [`apply`](Self::apply). A `UsageUpdate` that
+/// leaves out its model falls back to the agent's configured model, then the
+/// session model; one that leaves out its task falls back to the task
+/// started by the same submission, then the task its agent was spawned for.
+#[derive(Debug, Clone)]
+pub struct CostLedger {
+    pricing: PricingTable,
+    session_model: Option<String>,
+    agent_info: HashMap<AgentId, AgentInfo>,
+    submission_tasks: HashMap<SubmissionId, TaskId>,
+    pub total: CostTotals,
+    pub agents: HashMap<AgentId, CostTotals>,
+    pub roles: HashMap<AgentRole, CostTotals>,
+    pub models: BTreeMap<String, CostTotals>,
+    pub tasks: HashMap<TaskId, CostTotals>,
+}
+
+impl CostLedger {
+    pub fn new(pricing: PricingTable) -> Self {
+        Self {
+            pricing,
+            session_model: None,
+            agent_info: HashMap::new(),
+            submission_tasks: HashMap::new(),
+            total: CostTotals::default(),
+            agents: HashMap::new(),
+            roles: HashMap::new(),
+            models: BTreeMap::new(),
+            tasks: HashMap::new(),
+        }
+    }
+
+    pub fn pricing(&self) -> &PricingTable {
+        &self.pricing
+    }
+
+    /// Fold one event into the totals
+    pub fn apply(&mut self, event: &Event) {
+        match event {
+            Event::SessionConfigured { config, .. } => {
+                self.session_model = config.model.clone();
+            }
+            Event::TaskStarted { sub_id, task_id, .. } => {
+                self.submission_tasks.insert(sub_id.clone(), *task_id);
+            }
+            Event::AgentSpawned { sub_id, agent_id, role, config, .. } => {
+                let info = AgentInfo {
+                    role: role.clone(),
+                    model: config.model.clone(),
+                    task_id: self.submission_tasks.get(sub_id).copied(),
+                };
+                self.agent_info.insert(*agent_id, info);
+            }
+            Event::UsageUpdate { sub_id, agent_id, model, task_id, usage } => {
+                let info = agent_id.and_then(|id| self.agent_info.get(&id));
+                let model = model
+                    .clone()
+                    .or_else(|| info.and_then(|info| info.model.clone()))
+                    .or_else(|| self.session_model.clone());
+                let task_id = task_id
+                    .or_else(|| self.submission_tasks.get(sub_id).copied())
+                    .or_else(|| info.and_then(|info| info.task_id));
+                let role = info.map(|info| info.role.clone());
+                let cost = model.as_deref().and_then(|model| self.pricing.cost(model, usage));
+
+                self.total.add(usage, cost);
+                if let Some(agent_id) = agent_id {
+                    self.agents.entry(*agent_id).or_default().add(usage, cost);
+                }
+                if let Some(role) = role {
+                    self.roles.entry(role).or_default().add(usage, cost);
+                }
+                if let Some(model) = model {
+                    self.models.entry(model).or_default().add(usage, cost);
+                }
+                if let Some(task_id) = task_id {
+                    self.tasks.entry(task_id).or_default().add(usage, cost);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Build a ledger from a complete event stream
+    pub fn from_events<'a>(
+        pricing: PricingTable,
+        events: impl IntoIterator<Item = &'a Event>,
+    ) -> Self {
+        let mut ledger = Self::new(pricing);
+        for event in events {
+            ledger.apply(event);
+        }
+        ledger
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{AgentConfig, SessionConfig};
+
+    fn usd(text: &str) -> Usd {
+        text.parse().unwrap()
+    }
+
+    fn table() -> PricingTable {
+        PricingTable::from_json(
+            r#"{
+                "large*": {
+                    "input": "3",
+                    "output": "15",
+                    "cache_read": "0.30",
+                    "cache_write": "3.75"
+                },
+                "large-lite": { "input": 0.8, "output": 4 },
+                "small*": { "input": "0.25", "output": "1.25" }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn usage(input: u64, output: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens: input,
+            output_tokens: output,
+            total_tokens: input + output,
+            ..Default::default()
+        }
+    }
+
+    // === Usd Tests ===
+
+    #[test]
+    fn test_usd_parse_and_display() {
+        assert_eq!(usd("3").to_string(), "3.00");
+        assert_eq!(usd("0.0125").to_string(), "0.0125");
+        assert_eq!(usd("-1.5").to_string(), "-1.50");
+        assert_eq!(usd("0.000000000001").picos(), 1);
+        for bad in ["", ".5", "1.2.3", "abc", "1e3", "0.0000000000001"] {
+            assert!(bad.parse::<Usd>().is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_usd_exact_sums_and_rounding() {
+        let tenth = usd("0.1");
+        let sum: Usd = std::iter::repeat_n(tenth, 10).sum();
+        assert_eq!(sum, usd("1"));
+        assert_eq!(usd("0.125").round(2), usd("0.13"));
+        assert_eq!(usd("-0.125").round(2), usd("-0.13"));
+        assert_eq!(usd("2.5") - usd("0.75"), usd("1.75"));
+        assert_eq!(Usd::MAX + usd("1"), Usd::MAX);
+        assert_eq!(Usd::MIN - usd("1"), Usd::MIN);
+        let mut total = Usd::MAX;
+        total += Usd::MAX;
+        assert_eq!(total, Usd::MAX);
+    }
+
+    // === PricingTable Tests ===
+
+    #[test]
+    fn test_lookup_prefers_exact_then_longest_pattern() {
+        let table = table();
+        assert_eq!(table.get("large-lite").unwrap().input, usd("0.8"));
+        assert_eq!(table.get("large-2025").unwrap().input, usd("3"));
+        assert_eq!(table.get("small").unwrap().output, usd("1.25"));
+        assert!(table.get("medium").is_none());
+    }
+
+    #[test]
+    fn test_pattern_matches_across_slash() {
+        let mut table = PricingTable::new();
+        table.insert("*-large", ModelPricing { input: usd("3"), ..Default::default() });
+        assert_eq!(table.get("org/model-large").unwrap().input, usd("3"));
+    }
+
+    #[test]
+    fn test_cost_includes_cache_rates() {
+        let usage = TokenUsage {
+            input_tokens: 1_000,
+            output_tokens: 2_000,
+            cache_read_tokens: 10_000,
+            cache_write_tokens: 4_000,
+            total_tokens: 17_000,
+            estimated_cost_usd: None,
+        };
+        // 0.003 + 0.03 + 0.003 + 0.015
+        assert_eq!(table().cost("large-1", &usage), Some(usd("0.051")));
+        assert_eq!(table().cost("medium", &usage), None);
+    }
+
+    #[test]
+    fn test_cost_saturates() {
+        let usage =
+            TokenUsage { input_tokens: u64::MAX, output_tokens: u64::MAX, ..Default::default() };
+        let huge = ModelPricing { input: Usd::MAX, output: Usd::MAX, ..Default::default() };
+        assert_eq!(huge.cost(&usage), Usd(i128::MAX / TOKENS_PER_RATE));
+        let negative = ModelPricing { input: Usd::MIN, ..Default::default() };
+        assert_eq!(negative.cost(&usage), Usd((i128::MIN + TOKENS_PER_RATE / 2) / TOKENS_PER_RATE));
+    }
+
+    #[test]
+    fn test_serde_roundtrip() {
+        let table = table();
+        let json = serde_json::to_string(&table).unwrap();
+        assert!(json.contains(r#""input":"0.80""#));
+        assert_eq!(PricingTable::from_json(&json).unwrap(), table);
+        let cheap = r#"{ "x": { "input": "cheap", "output": "1" } }"#;
+        assert!(PricingTable::from_json(cheap).is_err());
+    }
+
+    #[test]
+    fn test_float_rates_round_to_picos() {
+        let json = r#"{ "x": { "input": 0.30000000000000004, "output": 1e-13 } }"#;
+        let table = PricingTable::from_json(json).unwrap();
+        let pricing = table.get("x").unwrap();
+        assert_eq!(pricing.input, "0.3".parse().unwrap());
+        assert_eq!(pricing.output, Usd::ZERO);
+        assert!(PricingTable::from_json(r#"{ "x": { "input": 1e300, "output": 1 } }"#).is_err());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_from_toml() {
+        let table = PricingTable::from_toml(
+            r#"
+            ["large*"]
+            input = 3
+            output = 15.0
+            cache_read = "0.30"
+            "#,
+        )
+        .unwrap();
+        let pricing = table.get("large-x").unwrap();
+        let rates = (pricing.input, pricing.output, pricing.cache_read);
+        assert_eq!(rates, (usd("3"), usd("15"), usd("0.3")));
+        assert_eq!(pricing.cache_write, Usd::ZERO);
+    }
+
+    // === CostLedger Tests ===
+
+    #[test]
+    fn test_ledger_breakdowns() {
+        let task = TaskId::new();
+        let task_sub = SubmissionId::new();
+        let lead = AgentId::new();
+        let worker = AgentId::new();
+        let spawn = |agent_id, role, model: Option<&str>| Event::AgentSpawned {
+            sub_id: task_sub.clone(),
+            agent_id,
+            parent_id: None,
+            role,
+            config: AgentConfig { model: model.map(Into::into), ..Default::default() },
+        };
+        let update = |agent_id, model: Option<&str>, task_id, usage| Event::UsageUpdate {
+            sub_id: SubmissionId::new(),
+            agent_id,
+            model: model.map(Into::into),
+            task_id,
+            usage,
+        };
+
+        let events = [
+            Event::SessionConfigured {
+                sub_id: SubmissionId::new(),
+                session_id: crate::ids::SessionId::new(),
+                config: SessionConfig { model: Some("large-1".into()), ..Default::default() },
+            },
+            Event::TaskStarted { sub_id: task_sub.clone(), task_id: task, prompt: "go".into() },
+            spawn(lead, AgentRole::Orchestrator, None),
+            spawn(worker, AgentRole::Worker, Some("small-1")),
+            update(Some(lead), None, None, usage(1_000_000, 0)),
+            update(Some(worker), None, None, usage(1_000_000, 1_000_000)),
+            update(Some(worker), Some("medium"), None, usage(500, 0)),
+            update(None, None, Some(TaskId::new()), usage(0, 100_000)),
+        ];
+        let ledger = CostLedger::from_events(table(), &events);
+
+        assert_eq!(ledger.agents[&lead].cost, usd("3"));
+        assert_eq!(ledger.agents[&worker].cost, usd("1.5"));
+        assert_eq!(ledger.agents[&worker].unpriced_tokens, 500);
+        assert_eq!(ledger.roles[&AgentRole::Worker].usage.input_tokens, 1_000_500);
+        assert_eq!(ledger.models["large-1"].cost, usd("4.5"));
+        assert_eq!(ledger.models["medium"].unpriced_tokens, 500);
+        assert_eq!(ledger.tasks[&task].cost, usd("4.5"));
+        assert_eq!(ledger.tasks.len(), 2);
+        assert_eq!(ledger.total.cost, usd("6"));
+    }
+}
@@ -0,0 +1,340 @@
+//! Text renderings of agent hierarchies and plans
+//!
+//! An [`AgentTree`] renders as a box-drawing tree for terminals and PR
+//! descriptions, or as a Graphviz DOT / Mermaid graph. A [`TaskPlan`]
+//! renders as a dependency graph with one node per step, coloured by
+//! [`StepComplexity`], and an edge `a -> b` for every `(a, b)` in
+//! `TaskPlan::dependencies` (`a` must finish before `b` starts).
+//!
+//! ```
+//! use warhorn::{AgentId, AgentRole, AgentStatus, AgentTree};
+//!
+//! let tree = AgentTree {
+//!     agent_id: AgentId::new(),
+//!     role: AgentRole::Orchestrator,
+//!     status: AgentStatus::Running,
+//!     task_summary: Some("Add auth".into()),
+//!     children: vec![],
+//! };
+//! assert!(tree.to_ascii().starts_with("● orchestrator"));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::models::{AgentRole, AgentStatus, AgentTree, StepComplexity, TaskPlan};
+
+/// Glyph shown before an agent in [`AgentTree::to_ascii`]
+pub fn status_glyph(status: &AgentStatus) -> char {
+    match status {
+        AgentStatus::Spawning => '◌',
+        AgentStatus::Initializing => '◐',
+        AgentStatus::Running => '●',
+        AgentStatus::Waiting { .. } => '⏸',
+        AgentStatus::Completed => '✓',
+        AgentStatus::Failed => '✗',
+        AgentStatus::Terminated => '■',
+    }
+}
+
+fn role_label(role: &AgentRole) -> String {
+    match role {
+        AgentRole::Orchestrator => "orchestrator".into(),
+        AgentRole::DomainLead { domain } => format!("{domain} lead"),
+        AgentRole::Worker => "worker".into(),
+        AgentRole::Specialist { specialty } => format!("{specialty} specialist"),
+        AgentRole::Scout => "scout".into(),
+        AgentRole::Reviewer => "reviewer".into(),
+        AgentRole::Custom { name } => name.clone(),
+    }
+}
+
+fn status_color(status: &AgentStatus) -> &'static str {
+    match status {
+        AgentStatus::Spawning | AgentStatus::Initializing => "#e2e3e5",
+        AgentStatus::Running => "#cfe2ff",
+        AgentStatus::Waiting { .. } => "#fff3cd",
+        AgentStatus::Completed => "#d1e7dd",
+        AgentStatus::Failed => "#f8d7da",
+        AgentStatus::Terminated => "#adb5bd",
+    }
+}
+
+fn complexity_color(complexity: StepComplexity) -> &'static str {
+    match complexity {
+        StepComplexity::Simple => "#d1e7dd",
+        StepComplexity::Moderate => "#fff3cd",
+        StepComplexity::Complex => "#f8d7da",
+    }
+}
+
+fn complexity_name(complexity: StepComplexity) -> &'static str {
+    match complexity {
+        StepComplexity::Simple => "simple",
+        StepComplexity::Moderate => "moderate",
+        StepComplexity::Complex => "complex",
+    }
+}
+
+/// One-line description of an agent: role, id, status detail and summary
+fn agent_label(node: &AgentTree) -> String {
+    let mut label = format!("{} ({})", role_label(&node.role), node.agent_id);
+    if let AgentStatus::Waiting { reason } = &node.status {
+        let _ = write!(label, " waiting: {reason}");
+    }
+    if let Some(summary) = &node.task_summary {
+        let _ = write!(label, " — {summary}");
+    }
+    label
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+impl AgentTree {
+    /// Render as a box-drawing tree with a status glyph per agent
+    pub fn to_ascii(&self) -> String {
+        let mut out = format!("{} {}\n", status_glyph(&self.status), agent_label(self));
+        ascii_children(self, "", &mut out);
+        out
+    }
+
+    /// Render as a Graphviz DOT digraph, filled by status
+    pub fn to_dot(&self) -> String {
+        let mut out =
+            String::from("digraph agents {\n  node [shape=box, style=\"rounded,filled\"];\n");
+        for (index, node) in self.iter_depth_first().enumerate() {
+            let _ = writeln!(
+                out,
+                "  a{index} [label=\"{} {}\", fillcolor=\"{}\"];",
+                status_glyph(&node.status),
+                dot_escape(&agent_label(node)),
+                status_color(&node.status)
+            );
+        }
+        for (parent, child) in self.edges() {
+            let _ = writeln!(out, "  a{parent} -> a{child};");
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render as a Mermaid flowchart, styled by status
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart TD\n");
+        for (index, node) in self.iter_depth_first().enumerate() {
+            let _ = writeln!(
+                out,
+                "  a{index}[\"{} {}\"]:::{}",
+                status_glyph(&node.status),
+                mermaid_escape(&agent_label(node)),
+                node.status.name()
+            );
+        }
+        for (parent, child) in self.edges() {
+            let _ = writeln!(out, "  a{parent} --> a{child}");
+        }
+        let styles: BTreeMap<&str, &str> = self
+            .iter_depth_first()
+            .map(|node| (node.status.name(), status_color(&node.status)))
+            .collect();
+        for (name, color) in styles {
+            let _ = writeln!(out, "  classDef {name} fill:{color}");
+        }
+        out
+    }
+
+    /// Parent/child pairs as indices into depth-first order
+    fn edges(&self) -> Vec<(usize, usize)> {
+        fn walk(node: &AgentTree, index: usize, next: &mut usize, edges: &mut Vec<(usize, usize)>) {
+            for child in &node.children {
+                let child_index = *next;
+                *next += 1;
+                edges.push((index, child_index));
+                walk(child, child_index, next, edges);
+            }
+        }
+        let mut edges = Vec::new();
+        walk(self, 0, &mut 1, &mut edges);
+        edges
+    }
+}
+
+fn ascii_children(node: &AgentTree, prefix: &str, out: &mut String) {
+    let count = node.children.len();
+    for (i, child) in node.children.iter().enumerate() {
+        let last = i + 1 == count;
+        let branch = if last { "└── " } else { "├── " };
+        let glyph = status_glyph(&child.status);
+        let _ = writeln!(out, "{prefix}{branch}{glyph} {}", agent_label(child));
+        let extension = if last { "    " } else { "│   " };
+        ascii_children(child, &format!("{prefix}{extension}"), out);
+    }
+}
+
+impl TaskPlan {
+    /// Render the step graph as a Graphviz DOT digraph
+    ///
+    /// Dependencies naming unknown steps are left out.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from(
+            "digraph plan {\n  rankdir=LR;\n  node [shape=box, style=\"rounded,filled\"];\n",
+        );
+        for (index, step) in self.steps.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "  s{index} [label=\"{}: {}\", fillcolor=\"{}\"];",
+                dot_escape(&step.id),
+                dot_escape(&step.description),
+                complexity_color(step.complexity)
+            );
+        }
+        for (from, to) in self.step_edges() {
+            let _ = writeln!(out, "  s{from} -> s{to};");
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Render the step graph as a Mermaid flowchart
+    ///
+    /// Dependencies naming unknown steps are left out.
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for (index, step) in self.steps.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "  s{index}[\"{}: {}\"]:::{}",
+                mermaid_escape(&step.id),
+                mermaid_escape(&step.description),
+                complexity_name(step.complexity)
+            );
+        }
+        for (from, to) in self.step_edges() {
+            let _ = writeln!(out, "  s{from} --> s{to}");
+        }
+        let complexities =
+            [StepComplexity::Simple, StepComplexity::Moderate, StepComplexity::Complex];
+        for complexity in complexities {
+            let _ = writeln!(
+                out,
+                "  classDef {} fill:{}",
+                complexity_name(complexity),
+                complexity_color(complexity)
+            );
+        }
+        out
+    }
+
+    /// Dependency pairs as indices into `steps`
+    fn step_edges(&self) -> Vec<(usize, usize)> {
+        let index = |id: &str| self.steps.iter().position(|step| step.id == id);
+        self.dependencies
+            .iter()
+            .filter_map(|(from, to)| Some((index(from)?, index(to)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::sample_plan;
+    use crate::ids::AgentId;
+    use pretty_assertions::assert_eq;
+    use uuid::Uuid;
+
+    fn node(n: u128, role: AgentRole, status: AgentStatus, summary: Option<&str>) -> AgentTree {
+        AgentTree {
+            agent_id: AgentId::from_uuid(Uuid::from_u128(n << 96)),
+            role,
+            status,
+            task_summary: summary.map(Into::into),
+            children: vec![],
+        }
+    }
+
+    fn tree() -> AgentTree {
+        let backend = AgentRole::DomainLead { domain: "backend".into() };
+        let mut lead = node(2, backend, AgentStatus::Running, None);
+        lead.children = vec![
+            node(3, AgentRole::Worker, AgentStatus::Completed, Some("Schema")),
+            node(4, AgentRole::Worker, AgentStatus::Waiting { reason: "approval".into() }, None),
+        ];
+        let mut root = node(1, AgentRole::Orchestrator, AgentStatus::Running, Some("Add auth"));
+        root.children = vec![lead, node(5, AgentRole::Scout, AgentStatus::Failed, None)];
+        root
+    }
+
+    // === AgentTree Tests ===
+
+    #[test]
+    fn test_ascii_tree() {
+        let expected = "\
+● orchestrator (agent-00000001) — Add auth
+├── ● backend lead (agent-00000002)
+│   ├── ✓ worker (agent-00000003) — Schema
+│   └── ⏸ worker (agent-00000004) waiting: approval
+└── ✗ scout (agent-00000005)
+";
+        assert_eq!(tree().to_ascii(), expected);
+    }
+
+    #[test]
+    fn test_agent_dot() {
+        let dot = tree().to_dot();
+        assert!(dot.starts_with("digraph agents {\n"));
+        assert!(dot.contains(
+            "a0 [label=\"● orchestrator (agent-00000001) — Add auth\", fillcolor=\"#cfe2ff\"];"
+        ));
+        for edge in ["a0 -> a1;", "a1 -> a2;", "a1 -> a3;", "a0 -> a4;"] {
+            assert!(dot.contains(edge), "missing {edge}");
+        }
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_agent_mermaid() {
+        let mermaid = tree().to_mermaid();
+        assert!(mermaid.starts_with("flowchart TD\n"));
+        assert!(mermaid.contains("a3[\"⏸ worker (agent-00000004) waiting: approval\"]:::waiting"));
+        assert!(mermaid.contains("a0 --> a4"));
+        assert_eq!(mermaid.matches("classDef running").count(), 1);
+        assert!(mermaid.contains("classDef failed fill:#f8d7da"));
+    }
+
+    // === TaskPlan Tests ===
+
+    #[test]
+    fn test_plan_dot() {
+        let mut plan = sample_plan();
+        plan.steps[1].description = "Add \"tests\"".into();
+        plan.dependencies.push(("1".into(), "missing".into()));
+
+        let dot = plan.to_dot();
+        assert!(dot.contains("s0 [label=\"1: Create auth module\", fillcolor=\"#f8d7da\"];"));
+        assert!(dot.contains("s1 [label=\"2: Add \\\"tests\\\"\", fillcolor=\"#d1e7dd\"];"));
+        assert_eq!(dot.matches("->").count(), 1);
+        assert!(dot.contains("s0 -> s1;"));
+    }
+
+    #[test]
+    fn test_plan_mermaid() {
+        let mermaid = sample_plan().to_mermaid();
+        let expected = "\
+flowchart LR
+  s0[\"1: Create auth module\"]:::complex
+  s1[\"2: Add tests\"]:::simple
+  s0 --> s1
+  classDef simple fill:#d1e7dd
+  classDef moderate fill:#fff3cd
+  classDef complex fill:#f8d7da
+";
+        assert_eq!(mermaid, expected);
+    }
+}
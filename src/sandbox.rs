@@ -0,0 +1,385 @@
+//! Evaluating filesystem access against a session's sandbox
+//!
+//! [`FilesystemSandbox`] answers "would this read or write be allowed?" from
+//! a `SessionConfig`, so the sandbox runtime and approval prompts agree.
+//!
+//! A path is expanded (`~`) and made absolute against the session `cwd`.
+//! Its lexical form, cleaned of `.` and `..` without touching the
+//! filesystem, is checked against the `FilesystemPolicy` deny globs and
+//! against the roots for the requested access. Its resolved form follows
+//! symlinks one component at a time and applies each `..` only after the
+//! links before it, as the kernel does: a path that only lies inside a root
+//! until its links are followed is a [`PathDecision::SymlinkEscape`], and a
+//! link into a denied location is denied. Dangling links are followed too,
+//! since writing through one creates its target.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::glob::glob_match;
+use crate::models::{FilesystemPolicy, SessionConfig};
+
+/// Kind of filesystem access being checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileAccess {
+    Read,
+    Write,
+}
+
+/// Result of checking one path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathDecision {
+    Allowed,
+    /// A deny glob matches the path or one of its parents
+    Denied { pattern: String },
+    /// Path is outside every root for this access
+    OutsideRoots,
+    /// Path is inside a root, but following symlinks leads outside
+    SymlinkEscape { resolved: PathBuf },
+    /// Relative path (or `~`) with no cwd (or home) to resolve it against
+    Unresolved,
+}
+
+impl PathDecision {
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, PathDecision::Allowed)
+    }
+}
+
+/// Filesystem rules of one session
+#[derive(Debug, Clone)]
+pub struct FilesystemSandbox {
+    enabled: bool,
+    cwd: Option<PathBuf>,
+    home: Option<PathBuf>,
+    policy: FilesystemPolicy,
+    extra_writable: Vec<PathBuf>,
+}
+
+/// Symlinks followed before giving up on a loop
+const MAX_SYMLINKS: usize = 40;
+
+impl FilesystemSandbox {
+    /// Rules from the session's sandbox config, with `~` taken from `$HOME`
+    pub fn new(config: &SessionConfig) -> Self {
+        Self {
+            enabled: config.sandbox.enabled,
+            cwd: config.cwd.clone(),
+            home: std::env::var_os("HOME").map(PathBuf::from),
+            policy: config.sandbox.filesystem.clone(),
+            extra_writable: config.sandbox.writable_paths.clone(),
+        }
+    }
+
+    /// Use `home` for `~` instead of `$HOME`
+    pub fn with_home(mut self, home: impl Into<PathBuf>) -> Self {
+        self.home = Some(home.into());
+        self
+    }
+
+    /// Check whether `path` may be accessed
+    ///
+    /// Everything is allowed when the sandbox is disabled.
+    pub fn evaluate(&self, path: impl AsRef<Path>, access: FileAccess) -> PathDecision {
+        if !self.enabled {
+            return PathDecision::Allowed;
+        }
+        let Some(absolute) = self.absolute(path.as_ref()) else {
+            return PathDecision::Unresolved;
+        };
+        let lexical = clean(&absolute);
+        let resolved = resolve_symlinks(&absolute);
+
+        for candidate in [&lexical, &resolved] {
+            if let Some(pattern) = self.denied_by(candidate) {
+                return PathDecision::Denied { pattern: pattern.to_string() };
+            }
+        }
+
+        let Some(roots) = self.roots(access) else {
+            return PathDecision::Allowed;
+        };
+        if !roots.iter().any(|root| lexical.starts_with(root)) {
+            return PathDecision::OutsideRoots;
+        }
+        if !roots.iter().any(|root| resolved.starts_with(resolve_symlinks(root))) {
+            return PathDecision::SymlinkEscape { resolved };
+        }
+        PathDecision::Allowed
+    }
+
+    /// Absolute form of `path`, still containing any `.` and `..`
+    fn absolute(&self, path: &Path) -> Option<PathBuf> {
+        let expanded = self.expand_home(path)?;
+        if expanded.is_absolute() {
+            return Some(expanded);
+        }
+        let cwd = self.expand_home(self.cwd.as_ref()?)?;
+        cwd.is_absolute().then(|| cwd.join(expanded))
+    }
+
+    /// Absolute, `.`/`..`-free form of `path`
+    fn normalize(&self, path: &Path) -> Option<PathBuf> {
+        self.absolute(path).map(|absolute| clean(&absolute))
+    }
+
+    fn expand_home(&self, path: &Path) -> Option<PathBuf> {
+        match path.strip_prefix("~") {
+            Ok(rest) => Some(self.home.as_ref()?.join(rest)),
+            Err(_) => Some(path.to_path_buf()),
+        }
+    }
+
+    /// Roots for `access`; `None` means unrestricted
+    fn roots(&self, access: FileAccess) -> Option<Vec<PathBuf>> {
+        let cwd = self.cwd.iter().filter(|_| self.policy.cwd_writable);
+        let writable = self
+            .policy
+            .writable_roots
+            .iter()
+            .chain(&self.extra_writable)
+            .chain(cwd)
+            .filter_map(|root| self.normalize(root));
+        match access {
+            FileAccess::Write => Some(writable.collect()),
+            FileAccess::Read if self.policy.readable_roots.is_empty() => None,
+            FileAccess::Read => Some(
+                self.policy
+                    .readable_roots
+                    .iter()
+                    .filter_map(|root| self.normalize(root))
+                    .chain(writable)
+                    .collect(),
+            ),
+        }
+    }
+
+    /// First deny glob matching `path` or one of its parents
+    fn denied_by(&self, path: &Path) -> Option<&str> {
+        self.policy
+            .deny
+            .iter()
+            .find(|pattern| {
+                let Some(glob) = self.deny_glob(pattern) else {
+                    return false;
+                };
+                path.ancestors().any(|ancestor| glob_match(&glob, &ancestor.to_string_lossy()))
+            })
+            .map(String::as_str)
+    }
+
+    fn deny_glob(&self, pattern: &str) -> Option<String> {
+        if pattern == "~" || pattern.starts_with("~/") {
+            let home = self.home.as_ref()?.to_string_lossy().trim_end_matches('/').to_string();
+            Some(format!("{home}{}", &pattern[1..]))
+        } else if pattern.starts_with('/') {
+            Some(pattern.to_string())
+        } else {
+            Some(format!("**/{pattern}"))
+        }
+    }
+}
+
+/// Remove `.` and resolve `..` lexically; `..` above the root stays at the root
+fn clean(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Follow symlinks in the absolute `path` one component at a time,
+/// including dangling ones, keeping any non-existent tail as written
+///
+/// A `..` removes the last component of the path resolved so far, so
+/// `link/..` is the parent of the link's target, not the link's directory.
+fn resolve_symlinks(path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    // Components still to walk, last one first
+    let reversed = |path: &Path| -> Vec<PathBuf> {
+        path.components().rev().map(|c| PathBuf::from(c.as_os_str())).collect()
+    };
+    let mut pending = reversed(path);
+    let mut followed = 0;
+    while let Some(next) = pending.pop() {
+        match next.components().next() {
+            Some(Component::CurDir) | None => {}
+            Some(Component::ParentDir) => {
+                resolved.pop();
+            }
+            Some(Component::Normal(name)) => {
+                let candidate = resolved.join(name);
+                let is_link = fs::symlink_metadata(&candidate)
+                    .is_ok_and(|meta| meta.file_type().is_symlink());
+                match fs::read_link(&candidate) {
+                    Ok(target) if is_link && followed < MAX_SYMLINKS => {
+                        followed += 1;
+                        if target.is_absolute() {
+                            resolved = PathBuf::new();
+                        }
+                        pending.extend(reversed(&target));
+                    }
+                    _ => resolved = candidate,
+                }
+            }
+            Some(root) => resolved = PathBuf::from(root.as_os_str()),
+        }
+    }
+    resolved
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::ids::SessionId;
+    use std::os::unix::fs::symlink;
+
+    struct Fixture {
+        root: PathBuf,
+    }
+
+    impl Fixture {
+        /// `root/{work,outside/deep,home/.ssh}` with links `work/out -> outside`,
+        /// `work/deep -> outside/deep`, `work/keys -> home/.ssh` and dangling
+        /// `work/drop -> outside/new.txt`
+        fn new() -> Self {
+            let root = std::env::temp_dir().join(format!("warhorn-sandbox-{}", SessionId::new()));
+            for dir in ["work/src", "outside/deep", "home/.ssh"] {
+                fs::create_dir_all(root.join(dir)).unwrap();
+            }
+            let root = fs::canonicalize(root).unwrap();
+            symlink(root.join("outside"), root.join("work/out")).unwrap();
+            symlink(root.join("outside/deep"), root.join("work/deep")).unwrap();
+            symlink(root.join("home/.ssh"), root.join("work/keys")).unwrap();
+            symlink(root.join("outside/new.txt"), root.join("work/drop")).unwrap();
+            Self { root }
+        }
+
+        fn sandbox(&self, policy: FilesystemPolicy) -> FilesystemSandbox {
+            let mut config =
+                SessionConfig { cwd: Some(self.root.join("work")), ..Default::default() };
+            config.sandbox.enabled = true;
+            config.sandbox.filesystem = policy;
+            FilesystemSandbox::new(&config).with_home(self.root.join("home"))
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+
+    // === Normalization Tests ===
+
+    #[test]
+    fn test_clean_paths() {
+        assert_eq!(clean(Path::new("/a/./b/../c")), PathBuf::from("/a/c"));
+        assert_eq!(clean(Path::new("/../../etc")), PathBuf::from("/etc"));
+    }
+
+    #[test]
+    fn test_relative_paths_need_cwd() {
+        let mut config = SessionConfig::default();
+        config.sandbox.enabled = true;
+        let sandbox = FilesystemSandbox::new(&config);
+        assert_eq!(sandbox.evaluate("src/lib.rs", FileAccess::Read), PathDecision::Unresolved);
+
+        config.sandbox.enabled = false;
+        let disabled = FilesystemSandbox::new(&config);
+        assert!(disabled.evaluate("src/lib.rs", FileAccess::Write).is_allowed());
+    }
+
+    // === Root Tests ===
+
+    #[test]
+    fn test_writes_stay_in_writable_roots() {
+        let fixture = Fixture::new();
+        let sandbox = fixture.sandbox(FilesystemPolicy::default());
+
+        assert!(sandbox.evaluate("src/main.rs", FileAccess::Write).is_allowed());
+        assert!(sandbox.evaluate("./src/../README.md", FileAccess::Write).is_allowed());
+        assert_eq!(sandbox.evaluate("../outside/x", FileAccess::Write), PathDecision::OutsideRoots);
+        assert!(sandbox.evaluate("../outside/x", FileAccess::Read).is_allowed());
+
+        let extra = FilesystemPolicy {
+            writable_roots: vec![fixture.root.join("outside")],
+            ..Default::default()
+        };
+        assert!(fixture.sandbox(extra).evaluate("../outside/x", FileAccess::Write).is_allowed());
+    }
+
+    #[test]
+    fn test_readable_roots_restrict_reads() {
+        let fixture = Fixture::new();
+        let policy = FilesystemPolicy {
+            readable_roots: vec![PathBuf::from("/usr")],
+            cwd_writable: false,
+            ..Default::default()
+        };
+        let sandbox = fixture.sandbox(policy);
+        assert!(sandbox.evaluate("/usr/share/dict", FileAccess::Read).is_allowed());
+        assert_eq!(sandbox.evaluate("src/main.rs", FileAccess::Read), PathDecision::OutsideRoots);
+        assert_eq!(sandbox.evaluate("src/main.rs", FileAccess::Write), PathDecision::OutsideRoots);
+    }
+
+    // === Deny Tests ===
+
+    #[test]
+    fn test_deny_globs() {
+        let fixture = Fixture::new();
+        let sandbox = fixture.sandbox(FilesystemPolicy::default());
+        let denied = |pattern: &str| PathDecision::Denied { pattern: pattern.into() };
+
+        assert_eq!(sandbox.evaluate("~/.ssh/id_ed25519", FileAccess::Read), denied("~/.ssh"));
+        assert_eq!(sandbox.evaluate(".env", FileAccess::Read), denied(".env"));
+        assert_eq!(sandbox.evaluate("src/.env", FileAccess::Write), denied(".env"));
+        assert!(sandbox.evaluate(".env.example", FileAccess::Read).is_allowed());
+    }
+
+    // === Symlink Tests ===
+
+    #[test]
+    fn test_symlink_escapes() {
+        let fixture = Fixture::new();
+        let sandbox = fixture.sandbox(FilesystemPolicy::default());
+
+        assert_eq!(
+            sandbox.evaluate("out/x", FileAccess::Write),
+            PathDecision::SymlinkEscape { resolved: fixture.root.join("outside/x") }
+        );
+        assert_eq!(
+            sandbox.evaluate("drop", FileAccess::Write),
+            PathDecision::SymlinkEscape { resolved: fixture.root.join("outside/new.txt") }
+        );
+        assert_eq!(
+            sandbox.evaluate("keys/id_ed25519", FileAccess::Read),
+            PathDecision::Denied { pattern: "~/.ssh".into() }
+        );
+        assert!(sandbox.evaluate("out/x", FileAccess::Read).is_allowed());
+    }
+
+    #[test]
+    fn test_parent_dir_after_symlink() {
+        let fixture = Fixture::new();
+        let sandbox = fixture.sandbox(FilesystemPolicy::default());
+
+        // Lexically this is `work/passwd`, but `deep/..` is `outside`
+        assert_eq!(
+            sandbox.evaluate("deep/../passwd", FileAccess::Write),
+            PathDecision::SymlinkEscape { resolved: fixture.root.join("outside/passwd") }
+        );
+        assert_eq!(
+            sandbox.evaluate("./src/../deep/../../home/.ssh/config", FileAccess::Read),
+            PathDecision::Denied { pattern: "~/.ssh".into() }
+        );
+        assert!(sandbox.evaluate("src/../README.md", FileAccess::Write).is_allowed());
+    }
+}
@@ -0,0 +1,336 @@
+//! JSON Schema for the wire protocol (requires the `schema` feature)
+//!
+//! Schemas follow draft 2020-12 and describe what a peer will *accept*:
+//! fields with a `#[serde(default)]` are optional and carry their default,
+//! `Option` fields may be omitted or `null`, and tagged enums such as
+//! [`Op`] and [`Event`] become a `oneOf` keyed on a `"type"` constant.
+//!
+//! ```
+//! let schema = warhorn::schema::protocol_schema();
+//! let defs = schema.get("$defs").unwrap();
+//! assert!(defs.get("Op").is_some());
+//! assert!(defs.get("Event").is_some());
+//! ```
+
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, Schema, SchemaGenerator};
+use serde_json::{json, Map, Value};
+
+use crate::envelope::{Envelope, ProtocolVersion};
+use crate::events::Event;
+use crate::models::*;
+use crate::ops::Op;
+
+/// `$schema` URI of the dialect used by every schema in this module
+pub const META_SCHEMA: &str = "https://json-schema.org/draft/2020-12/schema";
+
+fn generator() -> SchemaGenerator {
+    SchemaGenerator::new(SchemaSettings::draft2020_12().for_deserialize())
+}
+
+/// Standalone schema for any protocol type
+pub fn schema_for<T: JsonSchema>() -> Schema {
+    generator().into_root_schema_for::<T>()
+}
+
+/// Schema for a single [`Op`]
+pub fn op_schema() -> Schema {
+    schema_for::<Op>()
+}
+
+/// Schema for a single [`Event`]
+pub fn event_schema() -> Schema {
+    schema_for::<Event>()
+}
+
+/// Schema for an [`Envelope`] around `T`
+pub fn envelope_schema<T: JsonSchema>() -> Schema {
+    schema_for::<Envelope<T>>()
+}
+
+/// One document defining `Op`, `Event` and every model under `$defs`
+///
+/// The root validates either an `Op` or an `Event`; individual types can be
+/// referenced as `#/$defs/<Name>`.
+pub fn protocol_schema() -> Schema {
+    let mut generator = generator();
+    let op = generator.subschema_for::<Op>();
+    let event = generator.subschema_for::<Event>();
+
+    generator.subschema_for::<ProtocolVersion>();
+    generator.subschema_for::<SessionConfig>();
+    generator.subschema_for::<SessionSettings>();
+    generator.subschema_for::<ApprovalMode>();
+    generator.subschema_for::<ApprovalPolicy>();
+    generator.subschema_for::<ApprovalRule>();
+    generator.subschema_for::<ArgumentCondition>();
+    generator.subschema_for::<ApprovalAction>();
+    generator.subschema_for::<SandboxConfig>();
+    generator.subschema_for::<NetworkPolicy>();
+    generator.subschema_for::<FilesystemPolicy>();
+    generator.subschema_for::<Capability>();
+    generator.subschema_for::<McpServerConfig>();
+    generator.subschema_for::<McpTransport>();
+    generator.subschema_for::<AgentRole>();
+    generator.subschema_for::<AgentConfig>();
+    generator.subschema_for::<AgentStatus>();
+    generator.subschema_for::<AgentResult>();
+    generator.subschema_for::<TaskContext>();
+    generator.subschema_for::<TaskAssignment>();
+    generator.subschema_for::<TaskResult>();
+    generator.subschema_for::<ToolOutput>();
+    generator.subschema_for::<RiskLevel>();
+    generator.subschema_for::<PlanGranularity>();
+    generator.subschema_for::<TaskPlan>();
+    generator.subschema_for::<PlanStep>();
+    generator.subschema_for::<StepComplexity>();
+    generator.subschema_for::<PlanDiff>();
+    generator.subschema_for::<AgentTree>();
+    generator.subschema_for::<HierarchyOp>();
+    generator.subschema_for::<CheckpointMeta>();
+    generator.subschema_for::<TokenUsage>();
+    generator.subschema_for::<MessageType>();
+    generator.subschema_for::<ImageAttachment>();
+
+    let defs: Map<String, Value> = generator.take_definitions(true);
+    let description = format!("Ops and Events for protocol version {}", ProtocolVersion::current());
+    let root = json!({
+        "$schema": META_SCHEMA,
+        "title": "Warhorn Protocol",
+        "description": description,
+        "oneOf": [op, event],
+        "$defs": defs,
+    });
+    Schema::try_from(root).expect("protocol schema is an object")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{all_events, all_ops};
+
+    fn def<'a>(schema: &'a Schema, name: &str) -> &'a Value {
+        schema
+            .get("$defs")
+            .and_then(|defs| defs.get(name))
+            .unwrap_or_else(|| panic!("missing definition {name}"))
+    }
+
+    fn variant<'a>(schema: &'a Value, tag: &str) -> &'a Value {
+        schema["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|v| v["properties"]["type"]["const"] == tag)
+            .unwrap_or_else(|| panic!("missing variant {tag}"))
+    }
+
+    fn required(schema: &Value) -> Vec<&str> {
+        schema["required"]
+            .as_array()
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Minimal validator for the keywords our generated schemas use
+    ///
+    /// `format` and `pattern` are treated as annotations.
+    fn validate(root: &Value, schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+        let schema = match schema {
+            Value::Bool(true) => return Ok(()),
+            Value::Bool(false) => return Err(format!("{path}: no value is allowed")),
+            schema => schema,
+        };
+        if let Some(reference) = schema["$ref"].as_str() {
+            let name = reference.strip_prefix("#/$defs/").expect("local $ref");
+            validate(root, &root["$defs"][name], value, path)?;
+        }
+        if let Some(expected) = schema.get("const") {
+            if expected != value {
+                return Err(format!("{path}: expected {expected}, got {value}"));
+            }
+        }
+        if let Some(options) = schema["enum"].as_array() {
+            if !options.contains(value) {
+                return Err(format!("{path}: {value} is not one of {options:?}"));
+            }
+        }
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                other => vec![other.as_str().unwrap()],
+            };
+            let matches = |ty: &str| match ty {
+                "null" => value.is_null(),
+                "boolean" => value.is_boolean(),
+                "string" => value.is_string(),
+                "array" => value.is_array(),
+                "object" => value.is_object(),
+                "number" => value.is_number(),
+                "integer" => value.is_i64() || value.is_u64(),
+                other => panic!("unknown type {other}"),
+            };
+            if !types.iter().any(|ty| matches(ty)) {
+                return Err(format!("{path}: {value} is not {types:?}"));
+            }
+        }
+        if let Some(number) = value.as_f64() {
+            if schema["minimum"].as_f64().is_some_and(|min| number < min)
+                || schema["maximum"].as_f64().is_some_and(|max| number > max)
+            {
+                return Err(format!("{path}: {number} is out of range"));
+            }
+        }
+        if let Some(options) = schema["anyOf"].as_array() {
+            if !options.iter().any(|option| validate(root, option, value, path).is_ok()) {
+                return Err(format!("{path}: {value} matches no anyOf option"));
+            }
+        }
+        if let Some(options) = schema["oneOf"].as_array() {
+            let errors: Vec<String> = options
+                .iter()
+                .filter_map(|option| validate(root, option, value, path).err())
+                .collect();
+            if errors.len() + 1 != options.len() {
+                let matched = options.len() - errors.len();
+                return Err(format!("{path}: {matched} oneOf options match: {errors:?}"));
+            }
+        }
+        if let Value::Array(items) = value {
+            let prefix = schema["prefixItems"].as_array().map_or(&[][..], Vec::as_slice);
+            if schema["minItems"].as_u64().is_some_and(|min| (items.len() as u64) < min)
+                || schema["maxItems"].as_u64().is_some_and(|max| items.len() as u64 > max)
+            {
+                return Err(format!("{path}: wrong number of items"));
+            }
+            for (index, item) in items.iter().enumerate() {
+                let item_schema = prefix.get(index).or_else(|| schema.get("items"));
+                if let Some(item_schema) = item_schema {
+                    validate(root, item_schema, item, &format!("{path}/{index}"))?;
+                }
+            }
+        }
+        if let Value::Object(fields) = value {
+            for field in required(schema) {
+                if !fields.contains_key(field) {
+                    return Err(format!("{path}: missing required field {field}"));
+                }
+            }
+            let properties = schema["properties"].as_object();
+            for (name, field) in fields {
+                let field_path = format!("{path}/{name}");
+                let property = properties
+                    .and_then(|properties| properties.get(name))
+                    .or_else(|| schema.get("additionalProperties"));
+                if let Some(property) = property {
+                    validate(root, property, field, &field_path)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn assert_valid(schema: &Value, name: &str, value: &Value) {
+        let reference = json!({ "$ref": format!("#/$defs/{name}") });
+        if let Err(error) = validate(schema, &reference, value, "") {
+            panic!("{name} fixture does not validate: {error}\n{value:#}");
+        }
+    }
+
+    // === Schema Shape Tests ===
+
+    #[test]
+    fn test_draft_2020_12() {
+        assert_eq!(op_schema().get("$schema").unwrap(), META_SCHEMA);
+        assert_eq!(event_schema().get("$schema").unwrap(), META_SCHEMA);
+        assert_eq!(protocol_schema().get("$schema").unwrap(), META_SCHEMA);
+    }
+
+    #[test]
+    fn test_every_op_variant_is_tagged() {
+        let schema = op_schema().to_value();
+        for op in all_ops() {
+            let value = serde_json::to_value(&op).unwrap();
+            let tag = value["type"].as_str().unwrap();
+            let variant = variant(&schema, tag);
+            assert!(required(variant).contains(&"type"), "{tag} must require type");
+        }
+    }
+
+    #[test]
+    fn test_every_event_variant_is_tagged() {
+        let schema = event_schema().to_value();
+        for event in all_events() {
+            let value = serde_json::to_value(&event).unwrap();
+            let tag = value["type"].as_str().unwrap();
+            variant(&schema, tag);
+        }
+    }
+
+    #[test]
+    fn test_optional_and_default_fields() {
+        let schema = protocol_schema();
+
+        let config = def(&schema, "SessionConfig");
+        assert!(required(config).is_empty());
+        assert_eq!(config["properties"]["max_parallel_agents"]["default"], 8);
+
+        let sandbox = def(&schema, "SandboxConfig");
+        assert_eq!(sandbox["properties"]["enabled"]["default"], true);
+
+        let op = op_schema().to_value();
+        let user_input = variant(&op, "user_input");
+        let fields = required(user_input);
+        assert!(fields.contains(&"sub_id"));
+        assert!(fields.contains(&"prompt"));
+        assert!(!fields.contains(&"images"));
+        assert!(!fields.contains(&"context"));
+    }
+
+    #[test]
+    fn test_protocol_defines_every_model() {
+        let schema = protocol_schema();
+        let names = [
+            "Op",
+            "Event",
+            "ProtocolVersion",
+            "AgentRole",
+            "NetworkPolicy",
+            "TokenUsage",
+            "HierarchyOp",
+            "ApprovalPolicy",
+        ];
+        for name in names {
+            def(&schema, name);
+        }
+        assert_eq!(def(&schema, "ProtocolVersion")["type"], "string");
+    }
+
+    #[test]
+    fn test_every_fixture_validates() {
+        let schema = protocol_schema().to_value();
+        for op in all_ops() {
+            let value = serde_json::to_value(&op).unwrap();
+            assert_valid(&schema, "Op", &value);
+            validate(&schema, &schema, &value, "").unwrap();
+        }
+        for event in all_events() {
+            let value = serde_json::to_value(&event).unwrap();
+            assert_valid(&schema, "Event", &value);
+            validate(&schema, &schema, &value, "").unwrap();
+        }
+
+        let mut bad = serde_json::to_value(&all_ops()[0]).unwrap();
+        bad.as_object_mut().unwrap().remove("sub_id");
+        assert!(validate(&schema, &schema, &bad, "").is_err());
+    }
+
+    #[test]
+    fn test_envelope_schema() {
+        let schema = envelope_schema::<Op>().to_value();
+        let fields = required(&schema);
+        for field in ["version", "seq", "sent_at", "payload"] {
+            assert!(fields.contains(&field));
+        }
+    }
+}
@@ -0,0 +1,742 @@
+//! Session state folded from the event stream
+//!
+//! [`SessionState::apply`] is the one reducer every UI needs: it takes each
+//! `Event` in order and keeps a queryable picture of the session — which
+//! agents exist and what they are doing, how tasks ended, which tool calls are
+//! in flight or waiting for approval, which checkpoints have been seen, how
+//! far the plan has got, and how many tokens have been spent.
+//!
+//! ```
+//! use warhorn::state::SessionState;
+//! use warhorn::{AgentConfig, AgentId, AgentRole, Event, SubmissionId};
+//!
+//! let mut state = SessionState::new();
+//! let agent_id = AgentId::new();
+//! state.apply(&Event::AgentSpawned {
+//!     sub_id: SubmissionId::new(),
+//!     agent_id,
+//!     parent_id: None,
+//!     role: AgentRole::Orchestrator,
+//!     config: AgentConfig::default(),
+//! });
+//! assert!(state.agent(&agent_id).unwrap().is_active());
+//! ```
+
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+
+use crate::events::Event;
+use crate::ids::*;
+use crate::models::*;
+use crate::plan::PlanProgress;
+
+/// What the session knows about one agent
+#[derive(Debug, Clone)]
+pub struct AgentState {
+    pub agent_id: AgentId,
+    pub parent_id: Option<AgentId>,
+    pub role: AgentRole,
+    pub status: AgentStatus,
+    /// Configuration it was spawned with (default if only seen in a hierarchy snapshot)
+    pub config: AgentConfig,
+    /// Latest summary from `AgentWorking` or a hierarchy snapshot
+    pub task_summary: Option<String>,
+    /// Result reported by `AgentComplete`
+    pub result: Option<AgentResult>,
+    /// Reason given by `AgentTerminated`
+    pub termination_reason: Option<String>,
+    /// Tokens attributed to this agent by `UsageUpdate`
+    pub usage: TokenUsage,
+}
+
+impl AgentState {
+    /// Check whether the agent has not yet completed, failed or been terminated
+    pub fn is_active(&self) -> bool {
+        !self.status.is_terminal()
+    }
+}
+
+/// How a task ended, if it has
+#[derive(Debug, Clone)]
+pub enum TaskOutcome {
+    /// Still running
+    Running,
+    /// `TaskComplete` was received
+    Completed(TaskResult),
+    /// `TaskFailed` was received
+    Failed(String),
+    /// `TaskInterrupted` was received
+    Interrupted,
+}
+
+/// What the session knows about one task
+#[derive(Debug, Clone)]
+pub struct TaskState {
+    pub task_id: TaskId,
+    pub prompt: String,
+    /// Number of the last completed turn (0 before the first)
+    pub turns: u32,
+    /// Checkpoint written by the last completed turn
+    pub last_checkpoint: Option<CheckpointId>,
+    pub outcome: TaskOutcome,
+}
+
+impl TaskState {
+    /// Check whether the task is still running
+    pub fn is_running(&self) -> bool {
+        matches!(self.outcome, TaskOutcome::Running)
+    }
+}
+
+/// A tool call that has started but not finished
+#[derive(Debug, Clone)]
+pub struct ToolCallState {
+    pub call_id: CallId,
+    pub agent_id: AgentId,
+    pub tool_name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// A tool call waiting for the user to approve or deny it
+#[derive(Debug, Clone)]
+pub struct PendingApproval {
+    pub call_id: CallId,
+    pub agent_id: AgentId,
+    pub tool_name: String,
+    pub arguments: serde_json::Value,
+    pub description: String,
+    pub risk: RiskLevel,
+}
+
+/// A checkpoint seen in `TurnComplete`, `CheckpointSaved` or `CheckpointList`
+#[derive(Debug, Clone)]
+pub struct CheckpointRecord {
+    pub id: CheckpointId,
+    pub name: Option<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub task_id: Option<TaskId>,
+}
+
+/// Queryable session state built by folding events
+#[derive(Debug, Clone, Default)]
+pub struct SessionState {
+    pub session_id: Option<SessionId>,
+    /// Last configuration from `SessionConfigured`
+    pub config: Option<SessionConfig>,
+    pub settings: SessionSettings,
+    pub agents: HashMap<AgentId, AgentState>,
+    pub tasks: HashMap<TaskId, TaskState>,
+    /// Tool calls started and not yet completed or failed
+    pub tool_calls: HashMap<CallId, ToolCallState>,
+    /// Approval requests whose call has not yet completed or failed
+    pub pending_approvals: HashMap<CallId, PendingApproval>,
+    pub checkpoints: HashMap<CheckpointId, CheckpointRecord>,
+    /// Checkpoint most recently restored
+    pub restored_checkpoint: Option<CheckpointId>,
+    pub plan_mode: bool,
+    /// Latest plan from `PlanCreated`
+    pub plan: Option<TaskPlan>,
+    /// Step progress of the latest plan
+    pub plan_progress: PlanProgress,
+    /// Sum of every `UsageUpdate`
+    pub usage: TokenUsage,
+}
+
+impl SessionState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a state by applying every event in order
+    pub fn from_events<'a>(events: impl IntoIterator<Item = &'a Event>) -> Self {
+        let mut state = Self::new();
+        for event in events {
+            state.apply(event);
+        }
+        state
+    }
+
+    /// Fold one event into the state
+    ///
+    /// Events about unknown agents or tasks are applied where that makes
+    /// sense (a task first seen at `TaskFailed` is recorded as failed) and
+    /// ignored otherwise. `UsageUpdate` is treated as a delta, and a
+    /// `HierarchyUpdated` snapshot drops agents it does not list.
+    pub fn apply(&mut self, event: &Event) {
+        match event {
+            Event::Welcome { session_id, .. } => {
+                self.session_id = Some(*session_id);
+            }
+            Event::SessionConfigured { session_id, config, .. } => {
+                self.session_id = Some(*session_id);
+                self.config = Some(config.clone());
+            }
+            Event::SettingsUpdated { settings, .. } => {
+                self.settings = settings.clone();
+            }
+
+            Event::TaskStarted { task_id, prompt, .. } => {
+                self.tasks.insert(
+                    *task_id,
+                    TaskState {
+                        task_id: *task_id,
+                        prompt: prompt.clone(),
+                        turns: 0,
+                        last_checkpoint: None,
+                        outcome: TaskOutcome::Running,
+                    },
+                );
+            }
+            Event::TurnComplete { task_id, turn_number, checkpoint_id, .. } => {
+                let task = self.task_entry(*task_id);
+                task.turns = task.turns.max(*turn_number);
+                task.last_checkpoint = Some(*checkpoint_id);
+                self.checkpoints.entry(*checkpoint_id).or_insert(CheckpointRecord {
+                    id: *checkpoint_id,
+                    name: None,
+                    timestamp: None,
+                    task_id: Some(*task_id),
+                });
+            }
+            Event::TaskComplete { task_id, result, .. } => {
+                self.task_entry(*task_id).outcome = TaskOutcome::Completed(result.clone());
+            }
+            Event::TaskFailed { task_id, error, .. } => {
+                self.task_entry(*task_id).outcome = TaskOutcome::Failed(error.clone());
+            }
+            Event::TaskInterrupted { task_id, .. } => {
+                self.task_entry(*task_id).outcome = TaskOutcome::Interrupted;
+            }
+
+            Event::AgentSpawned { agent_id, parent_id, role, config, .. } => {
+                self.agents.insert(
+                    *agent_id,
+                    AgentState {
+                        agent_id: *agent_id,
+                        parent_id: *parent_id,
+                        role: role.clone(),
+                        status: AgentStatus::Spawning,
+                        config: config.clone(),
+                        task_summary: None,
+                        result: None,
+                        termination_reason: None,
+                        usage: TokenUsage::default(),
+                    },
+                );
+            }
+            Event::AgentWorking { agent_id, task_summary, .. } => {
+                if let Some(agent) = self.agents.get_mut(agent_id) {
+                    agent.task_summary = Some(task_summary.clone());
+                }
+            }
+            Event::AgentStatusChanged { agent_id, status, .. } => {
+                if let Some(agent) = self.agents.get_mut(agent_id) {
+                    agent.status = status.clone();
+                }
+            }
+            Event::AgentComplete { agent_id, result, .. } => {
+                if let Some(agent) = self.agents.get_mut(agent_id) {
+                    agent.status = if result.success {
+                        AgentStatus::Completed
+                    } else {
+                        AgentStatus::Failed
+                    };
+                    agent.result = Some(result.clone());
+                }
+            }
+            Event::AgentTerminated { agent_id, reason, .. } => {
+                if let Some(agent) = self.agents.get_mut(agent_id) {
+                    agent.status = AgentStatus::Terminated;
+                    agent.termination_reason = Some(reason.clone());
+                }
+                // A terminated agent will never report on its calls
+                self.tool_calls.retain(|_, call| call.agent_id != *agent_id);
+                self.pending_approvals.retain(|_, approval| approval.agent_id != *agent_id);
+            }
+            Event::AgentMessage { .. } => {}
+
+            Event::ToolCallStart { agent_id, call_id, tool_name, arguments, .. } => {
+                self.tool_calls.insert(
+                    *call_id,
+                    ToolCallState {
+                        call_id: *call_id,
+                        agent_id: *agent_id,
+                        tool_name: tool_name.clone(),
+                        arguments: arguments.clone(),
+                    },
+                );
+            }
+            Event::ApprovalRequired {
+                agent_id,
+                call_id,
+                tool_name,
+                arguments,
+                description,
+                risk,
+                ..
+            } => {
+                self.pending_approvals.insert(
+                    *call_id,
+                    PendingApproval {
+                        call_id: *call_id,
+                        agent_id: *agent_id,
+                        tool_name: tool_name.clone(),
+                        arguments: arguments.clone(),
+                        description: description.clone(),
+                        risk: *risk,
+                    },
+                );
+            }
+            // A denied approval ends in `ToolCallFailed`, possibly without a
+            // `ToolCallStart`, so both maps are cleared either way
+            Event::ToolCallComplete { call_id, .. } | Event::ToolCallFailed { call_id, .. } => {
+                self.tool_calls.remove(call_id);
+                self.pending_approvals.remove(call_id);
+            }
+
+            Event::HierarchyUpdated { root, .. } => {
+                let listed: HashSet<AgentId> =
+                    root.iter_depth_first().map(|node| node.agent_id).collect();
+                self.agents.retain(|agent_id, _| listed.contains(agent_id));
+                self.merge_tree(root, None);
+            }
+            Event::HierarchyPatch { ops, .. } => {
+                for op in ops {
+                    self.apply_hierarchy_op(op);
+                }
+            }
+
+            Event::CheckpointSaved { checkpoint_id, name, timestamp, .. } => {
+                let record = self.checkpoints.entry(*checkpoint_id).or_insert(CheckpointRecord {
+                    id: *checkpoint_id,
+                    name: None,
+                    timestamp: None,
+                    task_id: None,
+                });
+                record.name = name.clone();
+                record.timestamp = Some(*timestamp);
+            }
+            Event::CheckpointRestored { checkpoint_id, .. } => {
+                self.restored_checkpoint = Some(*checkpoint_id);
+            }
+            Event::CheckpointList { checkpoints, .. } => {
+                for meta in checkpoints {
+                    self.checkpoints.insert(
+                        meta.id,
+                        CheckpointRecord {
+                            id: meta.id,
+                            name: meta.name.clone(),
+                            timestamp: Some(meta.timestamp),
+                            task_id: meta.task_id,
+                        },
+                    );
+                }
+            }
+
+            Event::PlanModeChanged { enabled, .. } => {
+                self.plan_mode = *enabled;
+            }
+            Event::PlanCreated { plan, .. } => {
+                self.plan = Some(plan.clone());
+                self.plan_progress = PlanProgress::new(plan);
+            }
+            Event::PlanRevised { plan, .. } => {
+                self.plan = Some(plan.clone());
+                self.plan_progress.apply(event);
+            }
+            Event::PlanApproved { .. } => {}
+            Event::PlanStepStarted { .. }
+            | Event::PlanStepCompleted { .. }
+            | Event::PlanStepFailed { .. }
+            | Event::PlanStepSkipped { .. } => {
+                self.plan_progress.apply(event);
+            }
+
+            Event::Warning { .. }
+            | Event::Error { .. }
+            | Event::BudgetWarning { .. }
+            | Event::BudgetExceeded { .. } => {}
+
+            Event::UsageUpdate { agent_id, usage, .. } => {
+                self.usage.accumulate(usage);
+                if let Some(agent) = agent_id.and_then(|id| self.agents.get_mut(&id)) {
+                    agent.usage.accumulate(usage);
+                }
+            }
+        }
+    }
+
+    /// Look up an agent
+    pub fn agent(&self, agent_id: &AgentId) -> Option<&AgentState> {
+        self.agents.get(agent_id)
+    }
+
+    /// Agents whose parent is `parent_id` (`None` for roots)
+    pub fn children(&self, parent_id: Option<&AgentId>) -> Vec<&AgentState> {
+        self.agents
+            .values()
+            .filter(|agent| agent.parent_id.as_ref() == parent_id)
+            .collect()
+    }
+
+    /// Agents that have not completed, failed or been terminated
+    pub fn active_agents(&self) -> impl Iterator<Item = &AgentState> {
+        self.agents.values().filter(|agent| agent.is_active())
+    }
+
+    /// Look up a task
+    pub fn task(&self, task_id: &TaskId) -> Option<&TaskState> {
+        self.tasks.get(task_id)
+    }
+
+    /// Tasks that have not reached a terminal event
+    pub fn running_tasks(&self) -> impl Iterator<Item = &TaskState> {
+        self.tasks.values().filter(|task| task.is_running())
+    }
+
+    fn task_entry(&mut self, task_id: TaskId) -> &mut TaskState {
+        self.tasks.entry(task_id).or_insert(TaskState {
+            task_id,
+            prompt: String::new(),
+            turns: 0,
+            last_checkpoint: None,
+            outcome: TaskOutcome::Running,
+        })
+    }
+
+    fn apply_hierarchy_op(&mut self, op: &HierarchyOp) {
+        match op {
+            HierarchyOp::ReplaceRoot { root } => self.merge_tree(root, None),
+            HierarchyOp::InsertChild { parent_id, node, .. } => {
+                self.merge_tree(node, Some(*parent_id))
+            }
+            HierarchyOp::RemoveSubtree { agent_id } => {
+                let mut removed = vec![*agent_id];
+                while let Some(id) = removed.pop() {
+                    self.agents.remove(&id);
+                    removed.extend(
+                        self.agents
+                            .values()
+                            .filter(|agent| agent.parent_id == Some(id))
+                            .map(|agent| agent.agent_id),
+                    );
+                }
+            }
+            HierarchyOp::UpdateStatus { agent_id, status } => {
+                if let Some(agent) = self.agents.get_mut(agent_id) {
+                    agent.status = status.clone();
+                }
+            }
+            HierarchyOp::UpdateTaskSummary { agent_id, task_summary } => {
+                if let Some(agent) = self.agents.get_mut(agent_id) {
+                    agent.task_summary = task_summary.clone();
+                }
+            }
+        }
+    }
+
+    fn merge_tree(&mut self, node: &AgentTree, parent_id: Option<AgentId>) {
+        let agent = self.agents.entry(node.agent_id).or_insert(AgentState {
+            agent_id: node.agent_id,
+            parent_id,
+            role: node.role.clone(),
+            status: node.status.clone(),
+            config: AgentConfig::default(),
+            task_summary: None,
+            result: None,
+            termination_reason: None,
+            usage: TokenUsage::default(),
+        });
+        agent.parent_id = parent_id;
+        agent.role = node.role.clone();
+        agent.status = node.status.clone();
+        if node.task_summary.is_some() {
+            agent.task_summary = node.task_summary.clone();
+        }
+
+        for child in &node.children {
+            self.merge_tree(child, Some(node.agent_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{all_events, sample_tree, sample_usage};
+
+    fn spawned(agent_id: AgentId, parent_id: Option<AgentId>) -> Event {
+        Event::AgentSpawned {
+            sub_id: SubmissionId::new(),
+            agent_id,
+            parent_id,
+            role: AgentRole::Worker,
+            config: AgentConfig::default(),
+        }
+    }
+
+    fn tool_start(agent_id: AgentId, call_id: CallId) -> Event {
+        Event::ToolCallStart {
+            sub_id: SubmissionId::new(),
+            agent_id,
+            call_id,
+            tool_name: "shell".into(),
+            arguments: serde_json::json!({"command": "ls"}),
+        }
+    }
+
+    fn approval(agent_id: AgentId, call_id: CallId) -> Event {
+        Event::ApprovalRequired {
+            sub_id: SubmissionId::new(),
+            agent_id,
+            call_id,
+            tool_name: "shell".into(),
+            arguments: serde_json::json!({"command": "rm -rf build"}),
+            description: "Delete build".into(),
+            risk: RiskLevel::High,
+        }
+    }
+
+    // === Agent Tests ===
+
+    #[test]
+    fn test_agent_lifecycle() {
+        let root = AgentId::new();
+        let child = AgentId::new();
+        let mut state = SessionState::new();
+        state.apply(&spawned(root, None));
+        state.apply(&spawned(child, Some(root)));
+        state.apply(&Event::AgentStatusChanged {
+            sub_id: SubmissionId::new(),
+            agent_id: child,
+            status: AgentStatus::Running,
+        });
+        state.apply(&Event::AgentWorking {
+            sub_id: SubmissionId::new(),
+            agent_id: child,
+            task_summary: "Coding".into(),
+        });
+
+        assert_eq!(state.children(Some(&root)).len(), 1);
+        assert_eq!(state.children(None).len(), 1);
+        let agent = state.agent(&child).unwrap();
+        assert_eq!(agent.status, AgentStatus::Running);
+        assert_eq!(agent.task_summary.as_deref(), Some("Coding"));
+
+        state.apply(&Event::AgentComplete {
+            sub_id: SubmissionId::new(),
+            agent_id: child,
+            result: AgentResult {
+                success: false,
+                summary: "gave up".into(),
+                files_changed: vec![],
+                output: serde_json::Value::Null,
+            },
+        });
+        assert_eq!(state.agent(&child).unwrap().status, AgentStatus::Failed);
+        assert_eq!(state.active_agents().count(), 1);
+    }
+
+    #[test]
+    fn test_agent_terminated_drops_its_calls() {
+        let agent = AgentId::new();
+        let other = AgentId::new();
+        let mut state = SessionState::new();
+        state.apply(&spawned(agent, None));
+        state.apply(&spawned(other, None));
+        state.apply(&tool_start(agent, CallId::new()));
+        state.apply(&approval(agent, CallId::new()));
+        state.apply(&tool_start(other, CallId::new()));
+
+        state.apply(&Event::AgentTerminated {
+            sub_id: SubmissionId::new(),
+            agent_id: agent,
+            reason: "user".into(),
+        });
+
+        let terminated = state.agent(&agent).unwrap();
+        assert_eq!(terminated.status, AgentStatus::Terminated);
+        assert_eq!(terminated.termination_reason.as_deref(), Some("user"));
+        assert!(!terminated.is_active());
+        assert_eq!(state.tool_calls.len(), 1);
+        assert!(state.pending_approvals.is_empty());
+    }
+
+    #[test]
+    fn test_hierarchy_snapshot_merges_agents() {
+        let tree = sample_tree();
+        let mut state = SessionState::new();
+        state.apply(&Event::HierarchyUpdated { sub_id: SubmissionId::new(), root: tree.clone() });
+
+        assert_eq!(state.agents.len(), 3);
+        assert_eq!(state.children(Some(&tree.agent_id)).len(), 2);
+        assert_eq!(state.agent(&tree.agent_id).unwrap().task_summary.as_deref(), Some("Managing"));
+    }
+
+    #[test]
+    fn test_hierarchy_snapshot_drops_unlisted_agents() {
+        let mut tree = sample_tree();
+        let mut state = SessionState::new();
+        state.apply(&Event::HierarchyUpdated { sub_id: SubmissionId::new(), root: tree.clone() });
+        let stale = AgentId::new();
+        state.apply(&spawned(stale, Some(tree.agent_id)));
+
+        let dropped = tree.children.pop().unwrap().agent_id;
+        state.apply(&Event::HierarchyUpdated { sub_id: SubmissionId::new(), root: tree.clone() });
+
+        assert_eq!(state.agents.len(), 2);
+        assert!(state.agent(&dropped).is_none());
+        assert!(state.agent(&stale).is_none());
+    }
+
+    #[test]
+    fn test_hierarchy_patch_removes_subtree() {
+        let tree = sample_tree();
+        let mut state = SessionState::new();
+        state.apply(&Event::HierarchyUpdated { sub_id: SubmissionId::new(), root: tree.clone() });
+
+        let lead = tree.children[0].agent_id;
+        let scout = AgentId::new();
+        state.apply(&Event::HierarchyPatch {
+            sub_id: SubmissionId::new(),
+            ops: vec![
+                HierarchyOp::InsertChild {
+                    parent_id: lead,
+                    index: 0,
+                    node: AgentTree {
+                        agent_id: scout,
+                        role: AgentRole::Scout,
+                        status: AgentStatus::Spawning,
+                        task_summary: None,
+                        children: vec![],
+                    },
+                },
+                HierarchyOp::UpdateStatus { agent_id: scout, status: AgentStatus::Running },
+            ],
+        });
+        assert_eq!(state.agent(&scout).unwrap().parent_id, Some(lead));
+        assert_eq!(state.agent(&scout).unwrap().status, AgentStatus::Running);
+
+        state.apply(&Event::HierarchyPatch {
+            sub_id: SubmissionId::new(),
+            ops: vec![HierarchyOp::RemoveSubtree { agent_id: lead }],
+        });
+        assert!(state.agent(&lead).is_none());
+        assert!(state.agent(&scout).is_none());
+        assert_eq!(state.agents.len(), 2);
+    }
+
+    // === Tool Call Tests ===
+
+    #[test]
+    fn test_tool_call_failed_clears_approval() {
+        let agent = AgentId::new();
+        let call = CallId::new();
+        let mut state = SessionState::new();
+        state.apply(&approval(agent, call));
+        assert!(state.pending_approvals.contains_key(&call));
+
+        // Denied before it ever started
+        state.apply(&Event::ToolCallFailed {
+            sub_id: SubmissionId::new(),
+            agent_id: agent,
+            call_id: call,
+            tool_name: "shell".into(),
+            error: "denied".into(),
+        });
+        assert!(state.pending_approvals.is_empty());
+        assert!(state.tool_calls.is_empty());
+    }
+
+    #[test]
+    fn test_tool_call_complete() {
+        let agent = AgentId::new();
+        let call = CallId::new();
+        let mut state = SessionState::new();
+        state.apply(&tool_start(agent, call));
+        assert_eq!(state.tool_calls[&call].tool_name, "shell");
+
+        state.apply(&Event::ToolCallComplete {
+            sub_id: SubmissionId::new(),
+            agent_id: agent,
+            call_id: call,
+            tool_name: "shell".into(),
+            output: ToolOutput {
+                success: true,
+                content: String::new(),
+                data: None,
+                exit_code: Some(0),
+            },
+            duration_ms: 3,
+        });
+        assert!(state.tool_calls.is_empty());
+    }
+
+    // === Task Tests ===
+
+    #[test]
+    fn test_task_outcomes() {
+        let task = TaskId::new();
+        let checkpoint = CheckpointId::new();
+        let mut state = SessionState::new();
+        state.apply(&Event::TaskStarted {
+            sub_id: SubmissionId::new(),
+            task_id: task,
+            prompt: "go".into(),
+        });
+        state.apply(&Event::TurnComplete {
+            sub_id: SubmissionId::new(),
+            task_id: task,
+            turn_number: 1,
+            checkpoint_id: checkpoint,
+        });
+        assert_eq!(state.running_tasks().count(), 1);
+        assert_eq!(state.task(&task).unwrap().last_checkpoint, Some(checkpoint));
+        assert!(state.checkpoints.contains_key(&checkpoint));
+
+        state.apply(&Event::TaskInterrupted { sub_id: SubmissionId::new(), task_id: task });
+        assert!(matches!(state.task(&task).unwrap().outcome, TaskOutcome::Interrupted));
+        assert_eq!(state.running_tasks().count(), 0);
+    }
+
+    // === Usage Tests ===
+
+    #[test]
+    fn test_usage_accumulates() {
+        let agent = AgentId::new();
+        let mut state = SessionState::new();
+        state.apply(&spawned(agent, None));
+        for agent_id in [Some(agent), None] {
+            state.apply(&Event::UsageUpdate {
+                sub_id: SubmissionId::new(),
+                agent_id,
+                model: None,
+                task_id: None,
+                usage: sample_usage(),
+            });
+        }
+
+        assert_eq!(state.usage.total_tokens, 3000);
+        assert_eq!(state.usage.estimated_cost_usd, Some(0.025));
+        assert_eq!(state.agent(&agent).unwrap().usage.total_tokens, 1500);
+
+        let huge = TokenUsage { total_tokens: u64::MAX, ..Default::default() };
+        state.apply(&Event::UsageUpdate {
+            sub_id: SubmissionId::new(),
+            agent_id: Some(agent),
+            model: None,
+            task_id: None,
+            usage: huge,
+        });
+        assert_eq!(state.usage.total_tokens, u64::MAX);
+    }
+
+    #[test]
+    fn test_apply_every_event() {
+        let state = SessionState::from_events(&all_events());
+        assert!(state.session_id.is_some());
+        assert!(state.plan.is_some());
+        assert!(!state.checkpoints.is_empty());
+        assert_eq!(state.pending_approvals.len(), 1);
+    }
+}
@@ -0,0 +1,111 @@
+//! In-process transport over tokio mpsc channels
+//!
+//! Messages are moved between tasks as values; nothing is serialized.
+
+use tokio::sync::mpsc;
+
+use super::{Duplex, MessageSink, MessageStream};
+use crate::error::ProtocolError;
+use crate::events::Event;
+use crate::ops::Op;
+
+/// Sending half of an in-process channel
+#[derive(Debug)]
+pub struct ChannelSink<T> {
+    tx: Option<mpsc::Sender<T>>,
+}
+
+impl<T: Send> MessageSink<T> for ChannelSink<T> {
+    async fn send(&mut self, message: T) -> Result<(), ProtocolError> {
+        let tx = self.tx.as_ref().ok_or(ProtocolError::ChannelClosed)?;
+        tx.send(message).await.map_err(|_| ProtocolError::ChannelClosed)
+    }
+
+    async fn close(&mut self) -> Result<(), ProtocolError> {
+        self.tx = None;
+        Ok(())
+    }
+}
+
+/// Receiving half of an in-process channel
+#[derive(Debug)]
+pub struct ChannelStream<T> {
+    rx: mpsc::Receiver<T>,
+}
+
+impl<T: Send> MessageStream<T> for ChannelStream<T> {
+    async fn recv(&mut self) -> Result<T, ProtocolError> {
+        self.rx.recv().await.ok_or(ProtocolError::ChannelClosed)
+    }
+}
+
+/// Create a bounded one-way channel
+pub fn channel<T>(capacity: usize) -> (ChannelSink<T>, ChannelStream<T>) {
+    let (tx, rx) = mpsc::channel(capacity);
+    (ChannelSink { tx: Some(tx) }, ChannelStream { rx })
+}
+
+/// UI end of an in-process connection
+pub type ClientChannel = Duplex<ChannelSink<Op>, ChannelStream<Event>>;
+
+/// Orchestrator end of an in-process connection
+pub type ServerChannel = Duplex<ChannelSink<Event>, ChannelStream<Op>>;
+
+/// Create a connected UI/orchestrator pair, each direction bounded by `capacity`
+pub fn pair(capacity: usize) -> (ClientChannel, ServerChannel) {
+    let (op_tx, op_rx) = channel(capacity);
+    let (event_tx, event_rx) = channel(capacity);
+    (Duplex::new(op_tx, event_rx), Duplex::new(event_tx, op_rx))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SubmissionId;
+
+    #[tokio::test]
+    async fn test_pair_roundtrip() {
+        let (mut client, mut server) = pair(8);
+
+        let op = Op::user_input("hi");
+        client.send(op.clone()).await.unwrap();
+        let received = server.recv().await.unwrap();
+        assert_eq!(received.sub_id(), op.sub_id());
+
+        server
+            .send(Event::TaskInterrupted {
+                sub_id: received.sub_id().clone(),
+                task_id: crate::TaskId::new(),
+            })
+            .await
+            .unwrap();
+        let event = client.recv().await.unwrap();
+        assert_eq!(event.sub_id(), op.sub_id());
+    }
+
+    #[tokio::test]
+    async fn test_closed_channel() {
+        let (mut client, server) = pair(8);
+        drop(server);
+
+        assert!(matches!(
+            client.send(Op::interrupt()).await,
+            Err(ProtocolError::ChannelClosed)
+        ));
+        assert!(matches!(client.recv().await, Err(ProtocolError::ChannelClosed)));
+    }
+
+    #[tokio::test]
+    async fn test_close_ends_peer_stream() {
+        let (mut client, mut server) = pair(8);
+        client.send(Op::Undo { sub_id: SubmissionId::new() }).await.unwrap();
+        client.close().await.unwrap();
+
+        assert!(server.recv().await.is_ok());
+        assert!(matches!(server.recv().await, Err(ProtocolError::ChannelClosed)));
+        assert!(matches!(
+            client.send(Op::interrupt()).await,
+            Err(ProtocolError::ChannelClosed)
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+//! Async transports for `Op`s and `Event`s (requires the `transport` feature)
+//!
+//! A transport is a pair of halves: a [`MessageSink`] that sends messages and
+//! a [`MessageStream`] that receives them. The UI side sends `Op`s and
+//! receives `Event`s ([`OpSink`] + [`EventStream`]); the orchestrator side
+//! does the reverse ([`OpStream`] + [`EventSink`]).
+//!
+//! Implementations:
+//! - [`channel`]: in-process tokio mpsc pair, no serialization
+//! - [`unix`]: Unix domain socket, NDJSON framed
+//! - [`tcp`]: TCP, NDJSON framed
+//! - [`stdio`]: child-process stdin/stdout, NDJSON framed
+//! - `websocket`: WebSocket text or binary frames (requires the `websocket`
+//!   feature)
+//!
+//! Every implementation reports a peer that has gone away as
+//! [`ProtocolError::ChannelClosed`] and any other I/O failure as
+//! [`ProtocolError::TransportError`].
+
+use std::future::Future;
+use std::io;
+
+use crate::error::ProtocolError;
+use crate::events::Event;
+use crate::ops::Op;
+
+pub mod channel;
+pub mod stdio;
+pub mod stream;
+pub mod tcp;
+#[cfg(unix)]
+pub mod unix;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
+pub use stream::{NdjsonSink, NdjsonStream};
+
+/// Sending half of a transport
+pub trait MessageSink<T>: Send {
+    /// Send one message
+    fn send(&mut self, message: T) -> impl Future<Output = Result<(), ProtocolError>> + Send;
+
+    /// Close the sending half; the peer sees end of stream
+    fn close(&mut self) -> impl Future<Output = Result<(), ProtocolError>> + Send;
+}
+
+/// Receiving half of a transport
+pub trait MessageStream<T>: Send {
+    /// Receive the next message
+    ///
+    /// Returns [`ProtocolError::ChannelClosed`] once the peer has closed the
+    /// connection. A message that fails to decode is returned as an error
+    /// without closing the stream.
+    fn recv(&mut self) -> impl Future<Output = Result<T, ProtocolError>> + Send;
+}
+
+/// Sends `Op`s to the orchestrator
+pub trait OpSink: MessageSink<Op> {}
+impl<S: MessageSink<Op>> OpSink for S {}
+
+/// Receives `Op`s from the UI
+pub trait OpStream: MessageStream<Op> {}
+impl<S: MessageStream<Op>> OpStream for S {}
+
+/// Sends `Event`s to the UI
+pub trait EventSink: MessageSink<Event> {}
+impl<S: MessageSink<Event>> EventSink for S {}
+
+/// Receives `Event`s from the orchestrator
+pub trait EventStream: MessageStream<Event> {}
+impl<S: MessageStream<Event>> EventStream for S {}
+
+/// A sink and stream bundled into one bidirectional connection
+#[derive(Debug)]
+pub struct Duplex<Si, St> {
+    pub sink: Si,
+    pub stream: St,
+}
+
+impl<Si, St> Duplex<Si, St> {
+    pub fn new(sink: Si, stream: St) -> Self {
+        Self { sink, stream }
+    }
+
+    /// Split into the sending and receiving halves
+    pub fn split(self) -> (Si, St) {
+        (self.sink, self.stream)
+    }
+}
+
+impl<T: Send, Si: MessageSink<T>, St: Send> MessageSink<T> for Duplex<Si, St> {
+    fn send(&mut self, message: T) -> impl Future<Output = Result<(), ProtocolError>> + Send {
+        self.sink.send(message)
+    }
+
+    fn close(&mut self) -> impl Future<Output = Result<(), ProtocolError>> + Send {
+        self.sink.close()
+    }
+}
+
+impl<T, Si: Send, St: MessageStream<T>> MessageStream<T> for Duplex<Si, St> {
+    fn recv(&mut self) -> impl Future<Output = Result<T, ProtocolError>> + Send {
+        self.stream.recv()
+    }
+}
+
+/// Map an I/O error to a protocol error, treating a vanished peer as closed
+pub(crate) fn io_error(e: io::Error) -> ProtocolError {
+    match e.kind() {
+        io::ErrorKind::BrokenPipe
+        | io::ErrorKind::ConnectionReset
+        | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::UnexpectedEof => ProtocolError::ChannelClosed,
+        _ => ProtocolError::TransportError(e.to_string()),
+    }
+}
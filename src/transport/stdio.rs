@@ -0,0 +1,77 @@
+//! Child-process stdio transport
+//!
+//! The UI spawns the orchestrator as a child process and talks to it over
+//! the child's stdin and stdout. The orchestrator uses [`server`] to speak
+//! the same protocol on its own stdio.
+
+use std::process::Stdio;
+use tokio::io::{Stdin, Stdout};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+use super::{io_error, Duplex, NdjsonSink, NdjsonStream};
+use crate::error::ProtocolError;
+use crate::events::Event;
+use crate::ops::Op;
+
+/// UI end of a child-process connection
+pub type ChildClient = Duplex<NdjsonSink<ChildStdin, Op>, NdjsonStream<ChildStdout, Event>>;
+
+/// Orchestrator end of a stdio connection
+pub type StdioServer = Duplex<NdjsonSink<Stdout, Event>, NdjsonStream<Stdin, Op>>;
+
+/// Spawn `command` with piped stdin/stdout and connect to it
+///
+/// The child's stderr is inherited. The returned [`Child`] is killed when
+/// dropped.
+pub fn spawn(command: &mut Command) -> Result<(ChildClient, Child), ProtocolError> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(io_error)?;
+
+    let stdin = child.stdin.take().ok_or(ProtocolError::ChannelClosed)?;
+    let stdout = child.stdout.take().ok_or(ProtocolError::ChannelClosed)?;
+    Ok((Duplex::new(NdjsonSink::new(stdin), NdjsonStream::new(stdout)), child))
+}
+
+/// Serve the protocol on this process's own stdin/stdout
+pub fn server() -> StdioServer {
+    Duplex::new(
+        NdjsonSink::new(tokio::io::stdout()),
+        NdjsonStream::new(tokio::io::stdin()),
+    )
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::transport::{MessageSink, MessageStream};
+    use crate::SubmissionId;
+
+    #[tokio::test]
+    async fn test_child_roundtrip() {
+        // Echo back a warning carrying the sub_id of each undo op received.
+        let script = r#"while read -r line; do
+            id=$(printf '%s' "$line" | sed 's/.*"sub_id":"\([^"]*\)".*/\1/')
+            printf '{"type":"warning","sub_id":"%s","message":"ack"}\n' "$id"
+        done"#;
+        let (mut client, mut child) = spawn(Command::new("sh").arg("-c").arg(script)).unwrap();
+
+        let sub_id = SubmissionId::from_string("stdio-1");
+        client.send(Op::Undo { sub_id: sub_id.clone() }).await.unwrap();
+        let event = client.recv().await.unwrap();
+        assert_eq!(event.sub_id(), &sub_id);
+
+        client.close().await.unwrap();
+        assert!(child.wait().await.unwrap().success());
+        assert!(matches!(client.recv().await, Err(ProtocolError::ChannelClosed)));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_missing_binary() {
+        let result = spawn(&mut Command::new("/nonexistent/warhorn-test-binary"));
+        assert!(matches!(result, Err(ProtocolError::TransportError(_))));
+    }
+}
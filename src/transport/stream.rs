@@ -0,0 +1,181 @@
+//! NDJSON framing over any tokio `AsyncRead` / `AsyncWrite`
+//!
+//! The async counterpart of [`crate::codec`], shared by the socket and stdio
+//! transports.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+
+use super::{io_error, MessageSink, MessageStream};
+use crate::codec::{buffer_line_chunk, decode_line, DEFAULT_MAX_FRAME_SIZE};
+use crate::error::ProtocolError;
+
+/// Sends messages as newline-delimited JSON
+#[derive(Debug)]
+pub struct NdjsonSink<W, T> {
+    inner: Option<W>,
+    max_frame_size: usize,
+    _marker: PhantomData<fn(T)>,
+}
+
+impl<W: AsyncWrite + Unpin + Send, T: Serialize + Send> NdjsonSink<W, T> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner: Some(inner),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the maximum size of an encoded frame, excluding the newline
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// Unwrap the underlying writer, or `None` if the sink was closed
+    pub fn into_inner(self) -> Option<W> {
+        self.inner
+    }
+}
+
+impl<W: AsyncWrite + Unpin + Send, T: Serialize + Send> MessageSink<T> for NdjsonSink<W, T> {
+    async fn send(&mut self, message: T) -> Result<(), ProtocolError> {
+        let inner = self.inner.as_mut().ok_or(ProtocolError::ChannelClosed)?;
+
+        let mut line = serde_json::to_vec(&message)?;
+        if line.len() > self.max_frame_size {
+            return Err(ProtocolError::FrameTooLarge {
+                size: line.len(),
+                max: self.max_frame_size,
+            });
+        }
+        line.push(b'\n');
+
+        inner.write_all(&line).await.map_err(io_error)?;
+        inner.flush().await.map_err(io_error)
+    }
+
+    /// Shut down and drop the writer
+    ///
+    /// Dropping matters for pipes such as `ChildStdin`, where shutdown alone
+    /// does not close the file descriptor.
+    async fn close(&mut self) -> Result<(), ProtocolError> {
+        match self.inner.take() {
+            Some(mut inner) => inner.shutdown().await.map_err(io_error),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Receives messages from newline-delimited JSON
+#[derive(Debug)]
+pub struct NdjsonStream<R, T> {
+    inner: BufReader<R>,
+    max_frame_size: usize,
+    line_number: usize,
+    buf: Vec<u8>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: AsyncRead + Unpin + Send, T: DeserializeOwned + Send> NdjsonStream<R, T> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner: BufReader::new(inner),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            line_number: 0,
+            buf: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set the maximum size of a frame, excluding the newline
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// Read one line into `self.buf`, returning its full length
+    async fn read_line(&mut self) -> Result<Option<usize>, ProtocolError> {
+        self.buf.clear();
+        let mut size = 0;
+        let mut saw_any = false;
+
+        loop {
+            let available = self.inner.fill_buf().await.map_err(io_error)?;
+            if available.is_empty() {
+                return Ok(saw_any.then_some(size));
+            }
+            saw_any = true;
+
+            let chunk = buffer_line_chunk(available, &mut self.buf, self.max_frame_size);
+            size += chunk.len;
+
+            self.inner.consume(chunk.consumed);
+            if chunk.found_newline {
+                return Ok(Some(size));
+            }
+        }
+    }
+}
+
+impl<R, T> MessageStream<T> for NdjsonStream<R, T>
+where
+    R: AsyncRead + Unpin + Send,
+    T: DeserializeOwned + Send,
+{
+    async fn recv(&mut self) -> Result<T, ProtocolError> {
+        loop {
+            let size = self.read_line().await?.ok_or(ProtocolError::ChannelClosed)?;
+            self.line_number += 1;
+
+            let decoded = decode_line(&self.buf, size, self.max_frame_size, self.line_number);
+            if let Some(result) = decoded {
+                return result;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Event, Op};
+
+    #[tokio::test]
+    async fn test_duplex_roundtrip() {
+        let (a, b) = tokio::io::duplex(1024);
+        let mut sink = NdjsonSink::new(a);
+        let mut stream = NdjsonStream::<_, Op>::new(b);
+
+        let op = Op::user_input("hello");
+        sink.send(op.clone()).await.unwrap();
+        let received = stream.recv().await.unwrap();
+        assert_eq!(received.sub_id(), op.sub_id());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_line_then_recovers() {
+        let input: &[u8] = b"not json\n{\"type\":\"undo\",\"sub_id\":\"x\"}\n";
+        let mut stream = NdjsonStream::<_, Op>::new(input);
+
+        assert!(matches!(stream.recv().await, Err(ProtocolError::DeserializationError { .. })));
+        assert_eq!(stream.recv().await.unwrap().sub_id().as_str(), "x");
+        assert!(matches!(stream.recv().await, Err(ProtocolError::ChannelClosed)));
+    }
+
+    #[tokio::test]
+    async fn test_send_after_close() {
+        let (a, _b) = tokio::io::duplex(1024);
+        let mut sink = NdjsonSink::<_, Event>::new(a);
+        sink.close().await.unwrap();
+
+        let event = Event::TaskInterrupted {
+            sub_id: crate::SubmissionId::new(),
+            task_id: crate::TaskId::new(),
+        };
+        assert!(matches!(sink.send(event).await, Err(ProtocolError::ChannelClosed)));
+    }
+}
@@ -0,0 +1,88 @@
+//! TCP transport
+
+use std::net::SocketAddr;
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use super::{io_error, Duplex, NdjsonSink, NdjsonStream};
+use crate::error::ProtocolError;
+use crate::events::Event;
+use crate::ops::Op;
+
+/// UI end of a TCP connection
+pub type TcpClient = Duplex<NdjsonSink<OwnedWriteHalf, Op>, NdjsonStream<OwnedReadHalf, Event>>;
+
+/// Orchestrator end of a TCP connection
+pub type TcpServer = Duplex<NdjsonSink<OwnedWriteHalf, Event>, NdjsonStream<OwnedReadHalf, Op>>;
+
+/// Connect to an orchestrator listening at `addr`
+pub async fn connect(addr: impl ToSocketAddrs) -> Result<TcpClient, ProtocolError> {
+    let stream = TcpStream::connect(addr).await.map_err(io_error)?;
+    stream.set_nodelay(true).map_err(io_error)?;
+    let (read, write) = stream.into_split();
+    Ok(Duplex::new(NdjsonSink::new(write), NdjsonStream::new(read)))
+}
+
+/// Listens for UI connections over TCP
+#[derive(Debug)]
+pub struct Listener {
+    inner: TcpListener,
+}
+
+impl Listener {
+    /// Bind a listener at `addr`
+    pub async fn bind(addr: impl ToSocketAddrs) -> Result<Self, ProtocolError> {
+        let inner = TcpListener::bind(addr).await.map_err(io_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Address the listener is bound to
+    pub fn local_addr(&self) -> Result<SocketAddr, ProtocolError> {
+        self.inner.local_addr().map_err(io_error)
+    }
+
+    /// Accept the next UI connection
+    pub async fn accept(&self) -> Result<(TcpServer, SocketAddr), ProtocolError> {
+        let (stream, addr) = self.inner.accept().await.map_err(io_error)?;
+        stream.set_nodelay(true).map_err(io_error)?;
+        let (read, write) = stream.into_split();
+        Ok((Duplex::new(NdjsonSink::new(write), NdjsonStream::new(read)), addr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{MessageSink, MessageStream};
+    use crate::SubmissionId;
+
+    #[tokio::test]
+    async fn test_tcp_roundtrip() {
+        let listener = Listener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (mut conn, _) = listener.accept().await.unwrap();
+            while let Ok(op) = conn.recv().await {
+                conn.send(Event::Warning {
+                    sub_id: op.sub_id().clone(),
+                    message: "ack".into(),
+                    details: None,
+                })
+                .await
+                .unwrap();
+            }
+        });
+
+        let mut client = connect(addr).await.unwrap();
+        for i in 0..3 {
+            let sub_id = SubmissionId::from_string(format!("tcp-{i}"));
+            client.send(Op::Undo { sub_id: sub_id.clone() }).await.unwrap();
+            assert_eq!(client.recv().await.unwrap().sub_id(), &sub_id);
+        }
+
+        client.close().await.unwrap();
+        server.await.unwrap();
+        assert!(matches!(client.recv().await, Err(ProtocolError::ChannelClosed)));
+    }
+}
@@ -0,0 +1,85 @@
+//! Unix domain socket transport
+
+use std::path::Path;
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{UnixListener, UnixStream};
+
+use super::{io_error, Duplex, NdjsonSink, NdjsonStream};
+use crate::error::ProtocolError;
+use crate::events::Event;
+use crate::ops::Op;
+
+/// UI end of a Unix socket connection
+pub type UnixClient = Duplex<NdjsonSink<OwnedWriteHalf, Op>, NdjsonStream<OwnedReadHalf, Event>>;
+
+/// Orchestrator end of a Unix socket connection
+pub type UnixServer = Duplex<NdjsonSink<OwnedWriteHalf, Event>, NdjsonStream<OwnedReadHalf, Op>>;
+
+/// Connect to an orchestrator listening at `path`
+pub async fn connect(path: impl AsRef<Path>) -> Result<UnixClient, ProtocolError> {
+    let stream = UnixStream::connect(path).await.map_err(io_error)?;
+    let (read, write) = stream.into_split();
+    Ok(Duplex::new(NdjsonSink::new(write), NdjsonStream::new(read)))
+}
+
+/// Listens for UI connections on a Unix socket
+#[derive(Debug)]
+pub struct Listener {
+    inner: UnixListener,
+}
+
+impl Listener {
+    /// Bind a listener at `path`
+    pub fn bind(path: impl AsRef<Path>) -> Result<Self, ProtocolError> {
+        let inner = UnixListener::bind(path).map_err(io_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Accept the next UI connection
+    pub async fn accept(&self) -> Result<UnixServer, ProtocolError> {
+        let (stream, _) = self.inner.accept().await.map_err(io_error)?;
+        let (read, write) = stream.into_split();
+        Ok(Duplex::new(NdjsonSink::new(write), NdjsonStream::new(read)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::{MessageSink, MessageStream};
+    use crate::{SessionId, SubmissionId};
+
+    #[tokio::test]
+    async fn test_unix_roundtrip() {
+        let path = std::env::temp_dir().join(format!("warhorn-{}.sock", SessionId::new()));
+        let listener = Listener::bind(&path).unwrap();
+
+        let server = tokio::spawn(async move {
+            let mut conn = listener.accept().await.unwrap();
+            let op = conn.recv().await.unwrap();
+            conn.send(Event::Warning {
+                sub_id: op.sub_id().clone(),
+                message: "ack".into(),
+                details: None,
+            })
+            .await
+            .unwrap();
+        });
+
+        let mut client = connect(&path).await.unwrap();
+        let sub_id = SubmissionId::from_string("unix-1");
+        client.send(Op::Undo { sub_id: sub_id.clone() }).await.unwrap();
+        let event = client.recv().await.unwrap();
+        assert_eq!(event.sub_id(), &sub_id);
+
+        server.await.unwrap();
+        assert!(matches!(client.recv().await, Err(ProtocolError::ChannelClosed)));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_connect_missing_socket() {
+        let path = std::env::temp_dir().join(format!("warhorn-missing-{}.sock", SessionId::new()));
+        assert!(matches!(connect(&path).await, Err(ProtocolError::TransportError(_))));
+    }
+}
@@ -0,0 +1,466 @@
+//! WebSocket transport (requires the `websocket` feature)
+//!
+//! Carries `Op`s upstream and `Event`s downstream, one message per
+//! WebSocket frame. Text frames hold JSON; binary frames hold a
+//! [`Format`](crate::framing::Format) byte followed by the payload, so a
+//! peer may mix both. Intended for attaching browser dashboards to a running
+//! orchestrator over `ws://127.0.0.1`.
+//!
+//! Unlike the byte-stream transports a WebSocket connection is not split:
+//! [`WsConnection`] implements both [`MessageSink`] and [`MessageStream`],
+//! and keepalive pings are sent from inside [`MessageStream::recv`].
+
+use futures_util::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::time::{interval_at, Instant, Interval};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use super::{io_error, MessageSink, MessageStream};
+use crate::error::ProtocolError;
+use crate::events::Event;
+use crate::framing::Format;
+use crate::ops::Op;
+
+/// Close code sent when the peers speak incompatible protocol versions
+///
+/// Taken from the 4000-4999 range reserved for application use.
+pub const CLOSE_VERSION_MISMATCH: u16 = 4000;
+
+/// Longest close reason in bytes; a close frame's payload is at most 125
+/// bytes, two of which are the code
+pub const MAX_CLOSE_REASON: usize = 123;
+
+/// How outgoing messages are framed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WsEncoding {
+    /// JSON in text frames
+    #[default]
+    Text,
+    /// Format byte plus payload in binary frames
+    Binary(Format),
+}
+
+/// Ping schedule for detecting dead peers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keepalive {
+    /// How often to send a ping while idle
+    pub interval: Duration,
+    /// How long without any frame from the peer before giving up
+    pub timeout: Duration,
+}
+
+impl Default for Keepalive {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(15),
+            timeout: Duration::from_secs(45),
+        }
+    }
+}
+
+/// A WebSocket connection sending `Out` and receiving `In`
+#[derive(Debug)]
+pub struct WsConnection<S, Out, In> {
+    ws: WebSocketStream<S>,
+    encoding: WsEncoding,
+    keepalive: Option<Keepalive>,
+    ping_timer: Option<Interval>,
+    last_seen: Instant,
+    _marker: PhantomData<fn(Out) -> In>,
+}
+
+/// UI end of a WebSocket connection
+pub type WsClient = WsConnection<MaybeTlsStream<TcpStream>, Op, Event>;
+
+/// Orchestrator end of a WebSocket connection
+pub type WsServer = WsConnection<TcpStream, Event, Op>;
+
+impl<S, Out, In> WsConnection<S, Out, In>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    /// Wrap an established WebSocket stream
+    pub fn new(ws: WebSocketStream<S>) -> Self {
+        Self {
+            ws,
+            encoding: WsEncoding::default(),
+            keepalive: None,
+            ping_timer: None,
+            last_seen: Instant::now(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Set how outgoing messages are framed
+    pub fn with_encoding(mut self, encoding: WsEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Enable keepalive pings
+    pub fn with_keepalive(mut self, keepalive: Keepalive) -> Self {
+        let start = Instant::now() + keepalive.interval;
+        self.ping_timer = Some(interval_at(start, keepalive.interval));
+        self.keepalive = Some(keepalive);
+        self
+    }
+
+    /// Close the connection with an explicit close code and reason
+    ///
+    /// Reasons longer than [`MAX_CLOSE_REASON`] bytes are truncated.
+    pub async fn close_with(&mut self, code: CloseCode, reason: &str) -> Result<(), ProtocolError> {
+        let frame = CloseFrame { code, reason: truncate_reason(reason).to_owned().into() };
+        match self.ws.close(Some(frame)).await {
+            Ok(()) | Err(tungstenite::Error::ConnectionClosed) => Ok(()),
+            Err(e) => Err(ws_error(e)),
+        }
+    }
+
+    /// Close the connection, choosing the close code from `error`
+    pub async fn close_for(&mut self, error: &ProtocolError) -> Result<(), ProtocolError> {
+        let (code, reason) = close_frame_for(error);
+        self.close_with(code, &reason).await
+    }
+}
+
+impl<S, Out, In> MessageSink<Out> for WsConnection<S, Out, In>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+    Out: Serialize + Send,
+    In: Send,
+{
+    async fn send(&mut self, message: Out) -> Result<(), ProtocolError> {
+        let frame = match self.encoding {
+            WsEncoding::Text => Message::Text(serde_json::to_string(&message)?),
+            WsEncoding::Binary(format) => {
+                let mut bytes = vec![format.as_byte()];
+                bytes.extend(format.serialize(&message)?);
+                Message::Binary(bytes)
+            }
+        };
+        self.ws.send(frame).await.map_err(ws_error)
+    }
+
+    async fn close(&mut self) -> Result<(), ProtocolError> {
+        self.close_with(CloseCode::Normal, "").await
+    }
+}
+
+impl<S, Out, In> MessageStream<In> for WsConnection<S, Out, In>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+    Out: Send,
+    In: DeserializeOwned + Send,
+{
+    async fn recv(&mut self) -> Result<In, ProtocolError> {
+        loop {
+            let Self { ws, ping_timer, .. } = self;
+            let next = match ping_timer {
+                Some(timer) => tokio::select! {
+                    next = ws.next() => Some(next),
+                    _ = timer.tick() => None,
+                },
+                None => Some(ws.next().await),
+            };
+
+            let message = match next {
+                None => {
+                    self.ping().await?;
+                    continue;
+                }
+                Some(None) => return Err(ProtocolError::ChannelClosed),
+                Some(Some(Err(e))) => return Err(ws_error(e)),
+                Some(Some(Ok(message))) => message,
+            };
+            self.last_seen = Instant::now();
+
+            match message {
+                Message::Text(text) => {
+                    return serde_json::from_str(&text).map_err(|e| {
+                        ProtocolError::DeserializationError { message: e.to_string() }
+                    });
+                }
+                Message::Binary(bytes) => {
+                    let (&format, payload) = bytes.split_first().ok_or_else(|| {
+                        ProtocolError::DeserializationError { message: "empty binary frame".into() }
+                    })?;
+                    return Format::from_byte(format)?.deserialize(payload);
+                }
+                Message::Close(frame) => return Err(close_error(frame.as_ref())),
+                Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => {}
+            }
+        }
+    }
+}
+
+impl<S, Out, In> WsConnection<S, Out, In>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send,
+{
+    /// Send a keepalive ping, or give up if the peer has been silent too long
+    async fn ping(&mut self) -> Result<(), ProtocolError> {
+        let Some(keepalive) = self.keepalive else {
+            return Ok(());
+        };
+        if self.last_seen.elapsed() > keepalive.timeout {
+            let _ = self.close_with(CloseCode::Away, "keepalive timeout").await;
+            return Err(ProtocolError::TransportError("keepalive timeout".into()));
+        }
+        self.ws.send(Message::Ping(Vec::new())).await.map_err(ws_error)
+    }
+}
+
+/// Connect to an orchestrator at a `ws://` URL
+pub async fn connect(url: &str) -> Result<WsClient, ProtocolError> {
+    let (ws, _) = tokio_tungstenite::connect_async(url).await.map_err(ws_error)?;
+    Ok(WsConnection::new(ws))
+}
+
+/// Listens for WebSocket connections from UIs
+#[derive(Debug)]
+pub struct Listener {
+    inner: TcpListener,
+}
+
+impl Listener {
+    /// Bind a listener at `addr`
+    pub async fn bind(addr: impl ToSocketAddrs) -> Result<Self, ProtocolError> {
+        let inner = TcpListener::bind(addr).await.map_err(io_error)?;
+        Ok(Self { inner })
+    }
+
+    /// Address the listener is bound to
+    pub fn local_addr(&self) -> Result<SocketAddr, ProtocolError> {
+        self.inner.local_addr().map_err(io_error)
+    }
+
+    /// Accept the next connection and complete the WebSocket handshake
+    pub async fn accept(&self) -> Result<(WsServer, SocketAddr), ProtocolError> {
+        let (stream, addr) = self.inner.accept().await.map_err(io_error)?;
+        let ws = tokio_tungstenite::accept_async(stream).await.map_err(ws_error)?;
+        Ok((WsConnection::new(ws), addr))
+    }
+}
+
+/// Map a close frame from the peer to a protocol error
+pub fn close_error(frame: Option<&CloseFrame<'_>>) -> ProtocolError {
+    let Some(frame) = frame else {
+        return ProtocolError::ChannelClosed;
+    };
+    let reason = frame.reason.to_string();
+
+    match frame.code {
+        CloseCode::Normal | CloseCode::Away => ProtocolError::ChannelClosed,
+        CloseCode::Unsupported | CloseCode::Invalid => {
+            ProtocolError::DeserializationError { message: reason }
+        }
+        CloseCode::Library(CLOSE_VERSION_MISMATCH) => {
+            let (expected, actual) = reason
+                .strip_prefix("expected ")
+                .and_then(|rest| rest.split_once(", got "))
+                .map(|(e, a)| (e.to_string(), a.to_string()))
+                .unwrap_or_else(|| (String::new(), reason.clone()));
+            ProtocolError::VersionMismatch { expected, actual }
+        }
+        code => ProtocolError::TransportError(format!(
+            "connection closed with code {}: {}",
+            u16::from(code),
+            reason
+        )),
+    }
+}
+
+/// Choose the close code and reason to send when closing because of `error`
+///
+/// The reason is truncated to [`MAX_CLOSE_REASON`] bytes.
+pub fn close_frame_for(error: &ProtocolError) -> (CloseCode, String) {
+    let (code, reason) = match error {
+        ProtocolError::ChannelClosed => (CloseCode::Normal, String::new()),
+        ProtocolError::VersionMismatch { expected, actual } => (
+            CloseCode::Library(CLOSE_VERSION_MISMATCH),
+            format!("expected {}, got {}", expected, actual),
+        ),
+        ProtocolError::DeserializationError { .. }
+        | ProtocolError::UnknownOperation(_)
+        | ProtocolError::UnknownEvent(_)
+        | ProtocolError::UnsupportedFormat(_) => (CloseCode::Unsupported, error.to_string()),
+        ProtocolError::FrameTooLarge { .. } => (CloseCode::Size, error.to_string()),
+        _ => (CloseCode::Error, error.to_string()),
+    };
+    (code, truncate_reason(&reason).to_string())
+}
+
+/// Longest prefix of `reason` that fits in a close frame, cut at a char boundary
+fn truncate_reason(reason: &str) -> &str {
+    if reason.len() <= MAX_CLOSE_REASON {
+        return reason;
+    }
+    let end = (0..=MAX_CLOSE_REASON).rev().find(|&i| reason.is_char_boundary(i)).unwrap_or(0);
+    &reason[..end]
+}
+
+fn ws_error(e: tungstenite::Error) -> ProtocolError {
+    match e {
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            ProtocolError::ChannelClosed
+        }
+        tungstenite::Error::Io(e) => io_error(e),
+        e => ProtocolError::TransportError(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SubmissionId;
+
+    async fn loopback() -> (WsClient, WsServer) {
+        let listener = Listener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (client, server) = tokio::join!(connect(&url), listener.accept());
+        (client.unwrap(), server.unwrap().0)
+    }
+
+    fn ack(sub_id: &SubmissionId) -> Event {
+        Event::Warning {
+            sub_id: sub_id.clone(),
+            message: "ack".into(),
+            details: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_text_roundtrip() {
+        let (mut client, mut server) = loopback().await;
+
+        let op = Op::user_input("hello");
+        client.send(op.clone()).await.unwrap();
+        let received = server.recv().await.unwrap();
+        assert_eq!(received.sub_id(), op.sub_id());
+
+        server.send(ack(received.sub_id())).await.unwrap();
+        assert_eq!(client.recv().await.unwrap().sub_id(), op.sub_id());
+    }
+
+    #[tokio::test]
+    async fn test_binary_roundtrip() {
+        let (client, mut server) = loopback().await;
+        let mut client = client.with_encoding(WsEncoding::Binary(Format::Json));
+
+        let op = Op::interrupt();
+        client.send(op.clone()).await.unwrap();
+        assert_eq!(server.recv().await.unwrap().sub_id(), op.sub_id());
+    }
+
+    #[tokio::test]
+    async fn test_clean_close() {
+        let (mut client, mut server) = loopback().await;
+        client.close().await.unwrap();
+        assert!(matches!(server.recv().await, Err(ProtocolError::ChannelClosed)));
+    }
+
+    #[tokio::test]
+    async fn test_version_mismatch_close() {
+        let (mut client, mut server) = loopback().await;
+        let error = ProtocolError::VersionMismatch {
+            expected: "1.0.0".into(),
+            actual: "2.0.0".into(),
+        };
+        server.close_for(&error).await.unwrap();
+
+        match client.recv().await {
+            Err(ProtocolError::VersionMismatch { expected, actual }) => {
+                assert_eq!(expected, "1.0.0");
+                assert_eq!(actual, "2.0.0");
+            }
+            other => panic!("Expected VersionMismatch, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_long_close_reason_is_truncated() {
+        let (mut client, mut server) = loopback().await;
+        let error = ProtocolError::DeserializationError { message: "é".repeat(200) };
+        let (code, reason) = close_frame_for(&error);
+        assert_eq!(code, CloseCode::Unsupported);
+        assert!(reason.len() <= MAX_CLOSE_REASON);
+        assert!(reason.starts_with("Failed to deserialize"));
+
+        server.close_for(&error).await.unwrap();
+        assert!(matches!(client.recv().await, Err(ProtocolError::DeserializationError { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_times_out_silent_peer() {
+        let (client, mut server) = loopback().await;
+        let mut client = client.with_keepalive(Keepalive {
+            interval: Duration::from_millis(20),
+            timeout: Duration::from_millis(200),
+        });
+
+        // The server only answers pings while it is reading.
+        let server = tokio::spawn(async move {
+            let op = server.recv().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            let _ = server.send(ack(op.sub_id())).await;
+        });
+
+        let op = Op::interrupt();
+        client.send(op.clone()).await.unwrap();
+        let result = client.recv().await;
+        assert!(matches!(result, Err(ProtocolError::TransportError(_))), "{:?}", result);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_keepalive_pong_resets_timer() {
+        let (client, mut server) = loopback().await;
+        let mut client = client.with_keepalive(Keepalive {
+            interval: Duration::from_millis(20),
+            timeout: Duration::from_millis(200),
+        });
+
+        let op = Op::interrupt();
+        let sub_id = op.sub_id().clone();
+        let server = tokio::spawn(async move {
+            let op = server.recv().await.unwrap();
+            // Keep reading so pings get answered while we wait to reply.
+            let _ = tokio::time::timeout(Duration::from_millis(400), server.recv()).await;
+            server.send(ack(op.sub_id())).await.unwrap();
+            while server.recv().await.is_ok() {}
+        });
+
+        client.send(op).await.unwrap();
+        assert_eq!(client.recv().await.unwrap().sub_id(), &sub_id);
+        client.close().await.unwrap();
+        server.await.unwrap();
+    }
+
+    #[test]
+    fn test_close_error_mapping() {
+        let frame = |code, reason: &'static str| CloseFrame { code, reason: reason.into() };
+
+        assert!(matches!(close_error(None), ProtocolError::ChannelClosed));
+        assert!(matches!(
+            close_error(Some(&frame(CloseCode::Away, ""))),
+            ProtocolError::ChannelClosed
+        ));
+        assert!(matches!(
+            close_error(Some(&frame(CloseCode::Invalid, "bad json"))),
+            ProtocolError::DeserializationError { .. }
+        ));
+        assert!(matches!(
+            close_error(Some(&frame(CloseCode::Policy, "nope"))),
+            ProtocolError::TransportError(_)
+        ));
+    }
+}
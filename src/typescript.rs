@@ -0,0 +1,278 @@
+//! TypeScript declarations for the wire protocol (requires the `typescript`
+//! feature)
+//!
+//! [`generate`] renders the JSON Schema from [`crate::schema`] as a `.d.ts`
+//! module: `Op` and `Event` become discriminated unions on `type`, models
+//! become interfaces, and identifiers become branded strings so a `TaskId`
+//! cannot be passed where an `AgentId` is expected.
+//!
+//! Call it from a build script or a small binary in the UI repo:
+//!
+//! ```no_run
+//! std::fs::write("src/protocol.d.ts", warhorn::typescript::generate()).unwrap();
+//! ```
+
+use serde_json::{Map, Value};
+
+use crate::schema::protocol_schema;
+use crate::PROTOCOL_VERSION;
+
+/// Identifier types emitted as branded strings
+const BRANDED: &[&str] = &[
+    "AgentId",
+    "TaskId",
+    "CallId",
+    "SessionId",
+    "CheckpointId",
+    "SubmissionId",
+];
+
+/// Definitions emitted first, ahead of the alphabetical rest
+const LEADING: &[&str] = &["Op", "Event"];
+
+/// Generate the `.d.ts` module for `Op`, `Event` and every model
+pub fn generate() -> String {
+    let schema = protocol_schema();
+    let defs = schema
+        .get("$defs")
+        .and_then(Value::as_object)
+        .expect("protocol schema has $defs");
+
+    let mut out = format!(
+        "// Generated by warhorn for protocol version {PROTOCOL_VERSION}. Do not edit.\n"
+    );
+
+    let rest = defs.keys().filter(|name| !LEADING.contains(&name.as_str()));
+    for name in LEADING.iter().copied().chain(rest.map(String::as_str)) {
+        out.push('\n');
+        render_definition(&mut out, name, &defs[name]);
+    }
+    out
+}
+
+fn render_definition(out: &mut String, name: &str, schema: &Value) {
+    doc_comment(out, schema, "");
+
+    if BRANDED.contains(&name) {
+        out.push_str(&format!(
+            "export type {name} = string & {{ readonly __brand: \"{name}\" }};\n"
+        ));
+    } else if is_object(schema) && schema.get("properties").is_some() {
+        out.push_str(&format!("export interface {name} {}\n", render(schema, "")));
+    } else {
+        let ty = render(schema, "");
+        let separator = if ty.starts_with('\n') { "" } else { " " };
+        out.push_str(&format!("export type {name} ={separator}{ty};\n"));
+    }
+}
+
+/// Render a schema as a TypeScript type at the given indentation
+fn render(schema: &Value, indent: &str) -> String {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference.trim_start_matches("#/$defs/").to_string();
+    }
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+    if let Some(variants) = schema.get("oneOf").or_else(|| schema.get("anyOf")) {
+        return render_union(variants.as_array().into_iter().flatten(), indent);
+    }
+
+    match schema.get("type") {
+        Some(Value::String(ty)) => render_type(ty, schema, indent),
+        Some(Value::Array(types)) => {
+            let parts: Vec<String> = types
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|ty| render_type(ty, schema, indent))
+                .collect();
+            parts.join(" | ")
+        }
+        _ => "unknown".into(),
+    }
+}
+
+fn render_type(ty: &str, schema: &Value, indent: &str) -> String {
+    match ty {
+        "string" => "string".into(),
+        "integer" | "number" => "number".into(),
+        "boolean" => "boolean".into(),
+        "null" => "null".into(),
+        "array" => {
+            if let Some(items) = schema.get("prefixItems").and_then(Value::as_array) {
+                let items: Vec<String> = items.iter().map(|item| render(item, indent)).collect();
+                return format!("[{}]", items.join(", "));
+            }
+            let item = schema
+                .get("items")
+                .map(|items| render(items, indent))
+                .unwrap_or_else(|| "unknown".into());
+            if item.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                format!("{item}[]")
+            } else {
+                format!("Array<{item}>")
+            }
+        }
+        "object" => render_object(schema, indent),
+        _ => "unknown".into(),
+    }
+}
+
+fn render_object(schema: &Value, indent: &str) -> String {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return match schema.get("additionalProperties") {
+            Some(Value::Object(values)) => {
+                format!("Record<string, {}>", render(&Value::Object(values.clone()), indent))
+            }
+            _ => "Record<string, unknown>".into(),
+        };
+    };
+
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let inner = format!("{indent}  ");
+
+    let mut out = String::from("{\n");
+    for (name, property) in ordered(properties) {
+        doc_comment(&mut out, property, &inner);
+        let optional = if required.contains(&name.as_str()) { "" } else { "?" };
+        out.push_str(&format!(
+            "{inner}{}{optional}: {};\n",
+            property_name(name),
+            render(property, &inner)
+        ));
+    }
+    out.push_str(indent);
+    out.push('}');
+    out
+}
+
+fn render_union<'a>(variants: impl Iterator<Item = &'a Value>, indent: &str) -> String {
+    let parts: Vec<(Option<&str>, String)> = variants
+        .map(|variant| {
+            let description = variant.get("description").and_then(Value::as_str);
+            (description, render(variant, &format!("{indent}  ")))
+        })
+        .collect();
+
+    if parts.iter().all(|(_, ty)| !ty.contains('\n')) {
+        return parts.into_iter().map(|(_, ty)| ty).collect::<Vec<_>>().join(" | ");
+    }
+
+    let mut out = String::new();
+    for (description, ty) in parts {
+        out.push('\n');
+        if let Some(description) = description {
+            push_doc(&mut out, description, &format!("{indent}  "));
+        }
+        out.push_str(&format!("{indent}  | {ty}"));
+    }
+    out
+}
+
+/// Object properties with the `type` discriminant first
+fn ordered(properties: &Map<String, Value>) -> impl Iterator<Item = (&String, &Value)> {
+    let tag = properties.iter().filter(|(name, _)| *name == "type");
+    let rest = properties.iter().filter(|(name, _)| *name != "type");
+    tag.chain(rest)
+}
+
+fn property_name(name: &str) -> String {
+    let is_ident = name.chars().enumerate().all(|(i, c)| {
+        c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit())
+    });
+    if is_ident && !name.is_empty() {
+        name.to_string()
+    } else {
+        Value::String(name.to_string()).to_string()
+    }
+}
+
+fn is_object(schema: &Value) -> bool {
+    schema.get("type").and_then(Value::as_str) == Some("object")
+}
+
+fn doc_comment(out: &mut String, schema: &Value, indent: &str) {
+    if let Some(description) = schema.get("description").and_then(Value::as_str) {
+        push_doc(out, description, indent);
+    }
+}
+
+fn push_doc(out: &mut String, description: &str, indent: &str) {
+    let lines: Vec<&str> = description.lines().collect();
+    if let [line] = lines.as_slice() {
+        out.push_str(&format!("{indent}/** {line} */\n"));
+        return;
+    }
+    out.push_str(&format!("{indent}/**\n"));
+    for line in lines {
+        if line.is_empty() {
+            out.push_str(&format!("{indent} *\n"));
+        } else {
+            out.push_str(&format!("{indent} * {line}\n"));
+        }
+    }
+    out.push_str(&format!("{indent} */\n"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn golden_path() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/warhorn.d.ts")
+    }
+
+    // === Golden File Tests ===
+
+    /// Regenerate with `UPDATE_GOLDEN=1 cargo test --features typescript`
+    #[test]
+    fn test_matches_golden_file() {
+        let generated = generate();
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(golden_path(), &generated).unwrap();
+        }
+        let golden = std::fs::read_to_string(golden_path()).unwrap();
+        assert_eq!(golden, generated);
+    }
+
+    // === Output Shape Tests ===
+
+    #[test]
+    fn test_ids_are_branded() {
+        let ts = generate();
+        assert!(ts.contains(
+            "export type SubmissionId = string & { readonly __brand: \"SubmissionId\" };"
+        ));
+        assert!(ts.contains("export type AgentId = string & { readonly __brand: \"AgentId\" };"));
+    }
+
+    #[test]
+    fn test_discriminants() {
+        let ts = generate();
+        assert!(ts.contains("export type Op =\n"));
+        assert!(ts.contains("type: \"user_input\";"));
+        assert!(ts.contains("type: \"agent_spawned\";"));
+    }
+
+    #[test]
+    fn test_externally_tagged_enums() {
+        let ts = generate();
+        assert!(ts.contains("allowlist: string[];"));
+        assert!(ts.contains("domain_lead: {"));
+        assert!(ts.contains("| \"orchestrator\""));
+    }
+
+    #[test]
+    fn test_optional_fields() {
+        let ts = generate();
+        assert!(ts.contains("max_parallel_agents?: number;"));
+        assert!(ts.contains("task_summary?: string | null;"));
+        assert!(ts.contains("dependencies: Array<[string, string]>;"));
+    }
+}
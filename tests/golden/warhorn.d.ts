@@ -0,0 +1,955 @@
+// Generated by warhorn for protocol version 0.1.0. Do not edit.
+
+/**
+ * Operations sent FROM Lair UI TO Goblin orchestrator
+ *
+ * Each operation has an associated `SubmissionId` for correlation with events.
+ */
+export type Op =
+  /** Open the connection and negotiate version and capabilities */
+  | {
+    type: "hello";
+    /** Capabilities the client supports */
+    capabilities?: Capability[];
+    /** Name of the connecting client */
+    client_name: string;
+    /** Highest protocol version the client speaks */
+    protocol_version: ProtocolVersion;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Configure or reconfigure the session */
+  | {
+    type: "configure_session";
+    /** Session configuration */
+    config: SessionConfig;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Start a new task with user input */
+  | {
+    type: "user_input";
+    /** Resume from specific checkpoint */
+    checkpoint_id?: CheckpointId | null;
+    /** Optional context to include */
+    context?: TaskContext;
+    /** Optional images attached to prompt */
+    images?: ImageAttachment[];
+    /** User's prompt/request */
+    prompt: string;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Interrupt the current running task */
+  | {
+    type: "interrupt";
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+    /** Task to interrupt (None = current task) */
+    task_id?: TaskId | null;
+  }
+  /** Approve or deny a tool execution request */
+  | {
+    type: "exec_approval";
+    /** Whether to approve */
+    approved: boolean;
+    /** The call being approved/denied */
+    call_id: CallId;
+    /** Optional modification to command */
+    modified_command?: string | null;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Approve or deny an MCP tool call */
+  | {
+    type: "mcp_approval";
+    /** Whether to approve */
+    approved: boolean;
+    /** The call being approved/denied */
+    call_id: CallId;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Request to spawn a new agent (typically from orchestrator) */
+  | {
+    type: "spawn_agent";
+    /** Agent configuration */
+    config: AgentConfig;
+    /** Parent agent (None = root orchestrator) */
+    parent_id?: AgentId | null;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+    /** Task to assign */
+    task: TaskAssignment;
+  }
+  /** Terminate a specific agent */
+  | {
+    type: "terminate_agent";
+    /** Agent to terminate */
+    agent_id: AgentId;
+    /** Reason for termination */
+    reason?: string | null;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Send a message to a specific agent */
+  | {
+    type: "route_message";
+    /** Target agent */
+    agent_id: AgentId;
+    /** Message content */
+    content: string;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Save a checkpoint */
+  | {
+    type: "save_checkpoint";
+    /** Optional name for checkpoint */
+    name?: string | null;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Restore from a checkpoint */
+  | {
+    type: "restore_checkpoint";
+    /** Checkpoint to restore */
+    checkpoint_id: CheckpointId;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** List available checkpoints */
+  | {
+    type: "list_checkpoints";
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Undo to last auto-checkpoint */
+  | {
+    type: "undo";
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Toggle plan mode */
+  | {
+    type: "toggle_plan_mode";
+    /** Enable or disable */
+    enabled: boolean;
+    /** Plan granularity */
+    granularity?: PlanGranularity;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Approve the current plan and start executing it */
+  | {
+    type: "approve_plan";
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Reject the current plan and ask for a new one */
+  | {
+    type: "reject_plan";
+    /** What the new plan should do differently */
+    feedback?: string | null;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Replace the current plan with an edited one */
+  | {
+    type: "revise_plan";
+    /** The edited plan */
+    plan: TaskPlan;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  }
+  /** Update session settings */
+  | {
+    type: "update_settings";
+    /** Settings to update */
+    settings: SessionSettings;
+    /** Submission ID for correlation */
+    sub_id: SubmissionId;
+  };
+
+/**
+ * Events sent FROM Goblin orchestrator TO Lair UI
+ *
+ * Each event includes a `sub_id` correlating to the operation that triggered it.
+ */
+export type Event =
+  /** Reply to `Op::Hello` completing the handshake */
+  | {
+    type: "welcome";
+    /** Capabilities enabled for this connection */
+    accepted_capabilities?: Capability[];
+    /** Highest protocol version the server speaks */
+    server_version: ProtocolVersion;
+    session_id: SessionId;
+    sub_id: SubmissionId;
+  }
+  /** Session has been configured/reconfigured */
+  | {
+    type: "session_configured";
+    config: SessionConfig;
+    session_id: SessionId;
+    sub_id: SubmissionId;
+  }
+  /** Session settings updated */
+  | {
+    type: "settings_updated";
+    settings: SessionSettings;
+    sub_id: SubmissionId;
+  }
+  /** A new task has started */
+  | {
+    type: "task_started";
+    prompt: string;
+    sub_id: SubmissionId;
+    task_id: TaskId;
+  }
+  /** A task turn completed (checkpoint for resumption) */
+  | {
+    type: "turn_complete";
+    /** Checkpoint ID for this turn */
+    checkpoint_id: CheckpointId;
+    sub_id: SubmissionId;
+    task_id: TaskId;
+    turn_number: number;
+  }
+  /** Task completed successfully */
+  | {
+    type: "task_complete";
+    result: TaskResult;
+    sub_id: SubmissionId;
+    task_id: TaskId;
+  }
+  /** Task failed with error */
+  | {
+    type: "task_failed";
+    error: string;
+    sub_id: SubmissionId;
+    task_id: TaskId;
+  }
+  /** Task was interrupted */
+  | {
+    type: "task_interrupted";
+    sub_id: SubmissionId;
+    task_id: TaskId;
+  }
+  /** New agent spawned in hierarchy */
+  | {
+    type: "agent_spawned";
+    agent_id: AgentId;
+    config: AgentConfig;
+    parent_id?: AgentId | null;
+    role: AgentRole;
+    sub_id: SubmissionId;
+  }
+  /** Agent started working on task */
+  | {
+    type: "agent_working";
+    agent_id: AgentId;
+    sub_id: SubmissionId;
+    task_summary: string;
+  }
+  /** Agent status changed */
+  | {
+    type: "agent_status_changed";
+    agent_id: AgentId;
+    status: AgentStatus;
+    sub_id: SubmissionId;
+  }
+  /** Streaming message from agent */
+  | {
+    type: "agent_message";
+    agent_id: AgentId;
+    content: string;
+    /** Message type */
+    message_type: MessageType;
+    /** True if more content coming */
+    streaming: boolean;
+    sub_id: SubmissionId;
+  }
+  /** Agent completed its task */
+  | {
+    type: "agent_complete";
+    agent_id: AgentId;
+    result: AgentResult;
+    sub_id: SubmissionId;
+  }
+  /** Agent terminated */
+  | {
+    type: "agent_terminated";
+    agent_id: AgentId;
+    reason: string;
+    sub_id: SubmissionId;
+  }
+  /** Tool call started */
+  | {
+    type: "tool_call_start";
+    agent_id: AgentId;
+    arguments: unknown;
+    call_id: CallId;
+    sub_id: SubmissionId;
+    tool_name: string;
+  }
+  /** Tool execution requires approval */
+  | {
+    type: "approval_required";
+    agent_id: AgentId;
+    arguments: unknown;
+    call_id: CallId;
+    /** Human-readable description of what will happen */
+    description: string;
+    /** Risk level */
+    risk: RiskLevel;
+    sub_id: SubmissionId;
+    tool_name: string;
+  }
+  /** Tool call completed */
+  | {
+    type: "tool_call_complete";
+    agent_id: AgentId;
+    call_id: CallId;
+    duration_ms: number;
+    output: ToolOutput;
+    sub_id: SubmissionId;
+    tool_name: string;
+  }
+  /** Tool call failed */
+  | {
+    type: "tool_call_failed";
+    agent_id: AgentId;
+    call_id: CallId;
+    error: string;
+    sub_id: SubmissionId;
+    tool_name: string;
+  }
+  /** Agent hierarchy changed */
+  | {
+    type: "hierarchy_updated";
+    root: AgentTree;
+    sub_id: SubmissionId;
+  }
+  /**
+   * Incremental changes to the agent hierarchy
+   *
+   * Sent instead of `HierarchyUpdated` when `Capability::HierarchyPatches`
+   * was negotiated; apply with `AgentTree::apply`.
+   */
+  | {
+    type: "hierarchy_patch";
+    ops: HierarchyOp[];
+    sub_id: SubmissionId;
+  }
+  /** Checkpoint saved */
+  | {
+    type: "checkpoint_saved";
+    checkpoint_id: CheckpointId;
+    name?: string | null;
+    sub_id: SubmissionId;
+    timestamp: string;
+  }
+  /** Checkpoint restored */
+  | {
+    type: "checkpoint_restored";
+    checkpoint_id: CheckpointId;
+    sub_id: SubmissionId;
+  }
+  /** List of checkpoints */
+  | {
+    type: "checkpoint_list";
+    checkpoints: CheckpointMeta[];
+    sub_id: SubmissionId;
+  }
+  /** Plan mode toggled */
+  | {
+    type: "plan_mode_changed";
+    enabled: boolean;
+    granularity: PlanGranularity;
+    sub_id: SubmissionId;
+  }
+  /** Plan created from user request */
+  | {
+    type: "plan_created";
+    plan: TaskPlan;
+    sub_id: SubmissionId;
+  }
+  /** Plan replaced by a revision, from `RevisePlan` or replanning */
+  | {
+    type: "plan_revised";
+    /** Changes from the previous plan */
+    diff: PlanDiff;
+    plan: TaskPlan;
+    sub_id: SubmissionId;
+  }
+  /** Plan approved by `ApprovePlan`; execution follows */
+  | {
+    type: "plan_approved";
+    sub_id: SubmissionId;
+  }
+  /** Agent began working on a plan step */
+  | {
+    type: "plan_step_started";
+    agent_id: AgentId;
+    /** `PlanStep.id` of the step */
+    step_id: string;
+    sub_id: SubmissionId;
+    task_id: TaskId;
+  }
+  /** Plan step finished successfully */
+  | {
+    type: "plan_step_completed";
+    agent_id: AgentId;
+    /** `PlanStep.id` of the step */
+    step_id: string;
+    sub_id: SubmissionId;
+    task_id: TaskId;
+  }
+  /** Plan step failed */
+  | {
+    type: "plan_step_failed";
+    agent_id: AgentId;
+    error: string;
+    /** `PlanStep.id` of the step */
+    step_id: string;
+    sub_id: SubmissionId;
+    task_id: TaskId;
+  }
+  /** Plan step will not run */
+  | {
+    type: "plan_step_skipped";
+    /** Agent the step was assigned to, if it got that far */
+    agent_id?: AgentId | null;
+    reason?: string | null;
+    /** `PlanStep.id` of the step */
+    step_id: string;
+    sub_id: SubmissionId;
+    /** Task created for the step, if it got that far */
+    task_id?: TaskId | null;
+  }
+  /** Non-fatal warning */
+  | {
+    type: "warning";
+    details?: string | null;
+    message: string;
+    sub_id: SubmissionId;
+  }
+  /** Fatal error */
+  | {
+    type: "error";
+    message: string;
+    recoverable?: boolean;
+    sub_id: SubmissionId;
+  }
+  /** Token/cost usage update */
+  | {
+    type: "usage_update";
+    agent_id?: AgentId | null;
+    /** Model that consumed the tokens, if the producer knows it */
+    model?: string | null;
+    sub_id: SubmissionId;
+    /** Task the tokens were spent on, if the producer knows it */
+    task_id?: TaskId | null;
+    usage: TokenUsage;
+  }
+  /** Token use crossed a warning threshold of a budget */
+  | {
+    type: "budget_warning";
+    /** Agent whose budget it is; `None` for the session budget */
+    agent_id?: AgentId | null;
+    budget: number;
+    sub_id: SubmissionId;
+    /** Percentage of the budget that was reached */
+    threshold: number;
+    used: number;
+  }
+  /** Token use went over a budget */
+  | {
+    type: "budget_exceeded";
+    /** Agent whose budget it is; `None` for the session budget */
+    agent_id?: AgentId | null;
+    budget: number;
+    sub_id: SubmissionId;
+    used: number;
+  };
+
+/** Configuration for spawning an agent */
+export interface AgentConfig {
+  /** Can this agent spawn sub-agents? */
+  can_spawn?: boolean;
+  /** Working directory */
+  cwd?: string | null;
+  /** Max sub-agents this agent can spawn */
+  max_children?: number | null;
+  /** Model to use */
+  model?: string | null;
+  /** Agent role */
+  role?: AgentRole;
+  /** Token budget for this agent */
+  token_budget?: number | null;
+  /** Tools available to this agent */
+  tools?: string[];
+  /** Git worktree (for isolation) */
+  worktree?: string | null;
+}
+
+/** Unique identifier for an agent in the hierarchy */
+export type AgentId = string & { readonly __brand: "AgentId" };
+
+/** Result from an agent completing its task */
+export interface AgentResult {
+  /** Files changed */
+  files_changed?: string[];
+  /** Output data (structured) */
+  output?: unknown;
+  /** Success or failure */
+  success: boolean;
+  /** Summary of what was done */
+  summary: string;
+}
+
+/** Role of an agent in the hierarchy */
+export type AgentRole =
+  /** Top-level orchestrator */
+  | "orchestrator"
+  /** Domain lead (e.g., Frontend Lead, Backend Lead) */
+  | {
+    domain_lead: {
+      domain: string;
+    };
+  }
+  /** Worker agent */
+  | "worker"
+  /** Specialist agent (e.g., Security, Performance) */
+  | {
+    specialist: {
+      specialty: string;
+    };
+  }
+  /** Research/exploration agent */
+  | "scout"
+  /** Code review agent */
+  | "reviewer"
+  /** Custom role */
+  | {
+    custom: {
+      name: string;
+    };
+  };
+
+/** Current status of an agent */
+export type AgentStatus =
+  /** Being created */
+  | "spawning"
+  /** Loading context */
+  | "initializing"
+  /** Actively working */
+  | "running"
+  /** Waiting for input/approval/dependency */
+  | {
+    waiting: {
+      reason: string;
+    };
+  }
+  /** Task completed */
+  | "completed"
+  /** Task failed */
+  | "failed"
+  /** Manually terminated */
+  | "terminated";
+
+/** Tree representation of agent hierarchy */
+export interface AgentTree {
+  /** Agent info */
+  agent_id: AgentId;
+  /** Children in hierarchy */
+  children?: AgentTree[];
+  role: AgentRole;
+  status: AgentStatus;
+  task_summary?: string | null;
+}
+
+/** Outcome of an approval rule */
+export type ApprovalAction = "allow" | "deny" | "ask";
+
+/** Approval mode for tool execution */
+export type ApprovalMode = "always" | "never" | "risk_based" | "custom";
+
+/** Ordered approval rules; the first matching rule decides */
+export interface ApprovalPolicy {
+  /** Action when no rule matches */
+  default_action?: ApprovalAction;
+  /** Rules, checked in order */
+  rules?: ApprovalRule[];
+}
+
+/** One approval rule; every condition that is set must match */
+export interface ApprovalRule {
+  /** What to do when the rule matches */
+  action: ApprovalAction;
+  /** Conditions on the tool call arguments */
+  arguments?: ArgumentCondition[];
+  /**
+   * Path glob the session working directory must match (`*` stops at `/`,
+   * `**` does not)
+   */
+  cwd?: string | null;
+  /** Highest risk level this rule applies to */
+  max_risk?: RiskLevel | null;
+  /** Lowest risk level this rule applies to */
+  min_risk?: RiskLevel | null;
+  /** Name reported when this rule decides */
+  name?: string | null;
+  /** Agent roles this rule applies to; empty matches any role */
+  roles?: AgentRole[];
+  /**
+   * Tool name patterns (`*` matches any run of characters, `/` included);
+   * empty matches any tool
+   */
+  tools?: string[];
+}
+
+/** Condition on a tool argument addressed by a JSON pointer (e.g. `/command`) */
+export type ArgumentCondition =
+  /** Value is present */
+  | {
+    type: "exists";
+    pointer: string;
+  }
+  /** Value is missing */
+  | {
+    type: "absent";
+    pointer: string;
+  }
+  /** Value equals `value` */
+  | {
+    type: "equals";
+    pointer: string;
+    value: unknown;
+  }
+  /**
+   * Value is a string matching `pattern`, where `*` matches any run of
+   * characters, `/` included
+   */
+  | {
+    type: "matches";
+    pattern: string;
+    pointer: string;
+  };
+
+/** Unique identifier for a tool call */
+export type CallId = string & { readonly __brand: "CallId" };
+
+/** Optional protocol feature negotiated during the handshake */
+export type Capability = "streaming_deltas" | "hierarchy_patches" | "blob_attachments" | "unknown";
+
+/** Unique identifier for a checkpoint */
+export type CheckpointId = string & { readonly __brand: "CheckpointId" };
+
+/** Metadata for a checkpoint */
+export interface CheckpointMeta {
+  /** Checkpoint ID */
+  id: CheckpointId;
+  /** Optional name */
+  name?: string | null;
+  /** Size in bytes */
+  size_bytes: number;
+  /** Summary */
+  summary: string;
+  /** Task ID at checkpoint */
+  task_id?: TaskId | null;
+  /** When created */
+  timestamp: string;
+}
+
+/**
+ * Filesystem access rules for sandbox
+ *
+ * Relative roots are resolved against the session `cwd` and `~` against
+ * the home directory. Deny globs win over every root; a glob without a
+ * leading `/` or `~/` matches at any depth, so `.env` denies every `.env`
+ * file.
+ */
+export interface FilesystemPolicy {
+  /** Whether the session `cwd` is a writable root */
+  cwd_writable?: boolean;
+  /** Globs that may be neither read nor written */
+  deny?: string[];
+  /** Roots agents may read; empty allows reading anywhere */
+  readable_roots?: string[];
+  /** Roots agents may write (also readable) */
+  writable_roots?: string[];
+}
+
+/** A single change to an `AgentTree`, sent in `Event::HierarchyPatch` */
+export type HierarchyOp =
+  /** Replace the whole tree */
+  | {
+    type: "replace_root";
+    root: AgentTree;
+  }
+  /** Insert a subtree under `parent_id` at position `index` */
+  | {
+    type: "insert_child";
+    index: number;
+    node: AgentTree;
+    parent_id: AgentId;
+  }
+  /** Remove an agent and all of its descendants */
+  | {
+    type: "remove_subtree";
+    agent_id: AgentId;
+  }
+  /** Change an agent's status */
+  | {
+    type: "update_status";
+    agent_id: AgentId;
+    status: AgentStatus;
+  }
+  /** Change an agent's task summary */
+  | {
+    type: "update_task_summary";
+    agent_id: AgentId;
+    task_summary?: string | null;
+  };
+
+/** Image attached to a prompt */
+export interface ImageAttachment {
+  /** Base64 encoded image data */
+  data: string;
+  /** Optional filename */
+  filename?: string | null;
+  /** MIME type */
+  mime_type: string;
+}
+
+/** Configuration for an MCP server */
+export interface McpServerConfig {
+  /** Environment variables to set */
+  env?: Record<string, string>;
+  /** Unique identifier for this server */
+  id: string;
+  /** Display name */
+  name: string;
+  /** Transport type */
+  transport: McpTransport;
+}
+
+/** MCP transport configuration */
+export type McpTransport =
+  /** stdio-based transport */
+  | {
+    type: "stdio";
+    args?: string[];
+    command: string;
+  }
+  /** Socket-based transport */
+  | {
+    type: "socket";
+    path: string;
+  }
+  /** HTTP/SSE transport */
+  | {
+    type: "http";
+    url: string;
+  };
+
+/** Type of message from an agent */
+export type MessageType = "text" | "thinking" | "code" | "error" | "status" | "progress";
+
+/** Network access policy for sandbox */
+export type NetworkPolicy =
+  /** No network access */
+  | "none"
+  /** Localhost only */
+  | "localhost"
+  /** Specific hosts allowed: `host`, `*.domain`, IP or CIDR, each with an optional `:port` */
+  | {
+    allowlist: string[];
+  }
+  /** Full network access */
+  | "full";
+
+/**
+ * Step-level differences between two versions of a plan
+ *
+ * Steps are matched by id.
+ */
+export interface PlanDiff {
+  /** Steps only in the new plan, in new plan order */
+  added?: PlanStep[];
+  /** Steps in both plans whose contents differ, in new plan order */
+  changed?: PlanStepChange[];
+  /** Steps only in the old plan, in old plan order */
+  removed?: PlanStep[];
+}
+
+/** Granularity of task planning */
+export type PlanGranularity = "coarse" | "detailed" | "auto";
+
+/** A single step in a plan */
+export interface PlanStep {
+  /** Estimated complexity */
+  complexity?: StepComplexity;
+  /** Step description */
+  description: string;
+  /** Estimated token usage for this step */
+  estimated_tokens?: number | null;
+  /** Expected outcome */
+  expected_outcome: string;
+  /** Step ID */
+  id: string;
+}
+
+/** A plan step before and after a revision */
+export interface PlanStepChange {
+  after: PlanStep;
+  before: PlanStep;
+}
+
+/** A `major.minor.patch` protocol version */
+export type ProtocolVersion = string;
+
+/** Risk level for tool execution, ordered from least to most risky */
+export type RiskLevel = "none" | "low" | "medium" | "high" | "critical";
+
+/** Sandbox configuration */
+export interface SandboxConfig {
+  /** Enable sandboxing */
+  enabled?: boolean;
+  /** Filesystem read/write rules */
+  filesystem?: FilesystemPolicy;
+  /** Network access policy */
+  network?: NetworkPolicy;
+  /** Execution timeout */
+  timeout_secs?: number | null;
+  /** Additional writable paths */
+  writable_paths?: string[];
+}
+
+/** Configuration for a Goblin session */
+export interface SessionConfig {
+  /** Approval mode */
+  approval_mode?: ApprovalMode;
+  /** Rules used when `approval_mode` is `custom` */
+  approval_policy?: ApprovalPolicy | null;
+  /** Working directory for agents */
+  cwd?: string | null;
+  /** Custom system instructions */
+  instructions?: string | null;
+  /** Max parallel agents */
+  max_parallel_agents?: number;
+  /** MCP servers to connect */
+  mcp_servers?: McpServerConfig[];
+  /** Model to use for orchestrator */
+  model?: string | null;
+  /** Sandbox policy */
+  sandbox?: SandboxConfig;
+  /** Token budget for the whole session, across all agents */
+  token_budget?: number | null;
+}
+
+/** Unique identifier for a session */
+export type SessionId = string & { readonly __brand: "SessionId" };
+
+/** Session runtime settings (modifiable without reconfigure) */
+export interface SessionSettings {
+  /** Plan mode granularity */
+  plan_granularity?: PlanGranularity;
+  /** Show rate limit info in UI */
+  show_rate_limit?: boolean;
+  /** Number of parallel subagents */
+  subagent_concurrency?: number | null;
+}
+
+/** Complexity of a plan step */
+export type StepComplexity = "simple" | "moderate" | "complex";
+
+/** Submission ID for correlating operations with events */
+export type SubmissionId = string & { readonly __brand: "SubmissionId" };
+
+/** Task assigned to an agent */
+export interface TaskAssignment {
+  /** Context for this task */
+  context?: TaskContext;
+  /** Expected deliverables */
+  deliverables?: string[];
+  /** Dependencies on other tasks */
+  dependencies?: TaskId[];
+  /** Task description */
+  description: string;
+  /** Task ID */
+  task_id: TaskId;
+}
+
+/** Context provided with a task */
+export interface TaskContext {
+  /** Current working directory */
+  cwd?: string | null;
+  /** Files to include as context */
+  files?: string[];
+  /** Additional context from Grimoire */
+  memory_context?: string[];
+  /** Custom metadata */
+  metadata?: Record<string, unknown>;
+}
+
+/** Unique identifier for a task */
+export type TaskId = string & { readonly __brand: "TaskId" };
+
+/** A task decomposition plan */
+export interface TaskPlan {
+  /** Agent assignments */
+  agent_assignments: Record<string, AgentRole>;
+  /** Dependency graph */
+  dependencies: Array<[string, string]>;
+  /** Estimated token usage */
+  estimated_tokens?: number;
+  /** Original request */
+  original_request: string;
+  /** Decomposed steps */
+  steps: PlanStep[];
+}
+
+/** Result of a completed task */
+export interface TaskResult {
+  /** Files changed */
+  files_changed?: string[];
+  /** Success or failure */
+  success: boolean;
+  /** Summary */
+  summary: string;
+  /** Task ID */
+  task_id: TaskId;
+  /** Token usage */
+  token_usage?: TokenUsage;
+}
+
+/** Token usage statistics */
+export interface TokenUsage {
+  /** Input tokens read from the prompt cache, not counted in `input_tokens` */
+  cache_read_tokens?: number;
+  /** Input tokens written to the prompt cache, not counted in `input_tokens` */
+  cache_write_tokens?: number;
+  /** Estimated cost (USD) */
+  estimated_cost_usd?: number | null;
+  /** Input tokens */
+  input_tokens: number;
+  /** Output tokens */
+  output_tokens: number;
+  /** Total tokens */
+  total_tokens: number;
+}
+
+/** Output from a tool execution */
+export interface ToolOutput {
+  /** Output content */
+  content: string;
+  /** Structured data */
+  data?: unknown;
+  /** Exit code (for shell commands) */
+  exit_code?: number | null;
+  /** Success or failure */
+  success: boolean;
+}